@@ -18,6 +18,10 @@ pub enum UtilError {
     StatementFalse,
     #[error("NotRentExempt")]
     NotRentExempt,
+    #[error("AccountFrozen")]
+    AccountFrozen,
+    #[error("VersionMismatch")]
+    VersionMismatch,
 }
 
 impl From<UtilError> for ProgramError {
@@ -36,10 +40,34 @@ pub enum RouletteError {
     SuspiciousTransaction,
     #[error("InvalidSlot")]
     InvalidSlot,
+    #[error("TooManyBets")]
+    TooManyBets,
+    #[error("Paused")]
+    Paused,
+    #[error("OwnerCannotPlay")]
+    OwnerCannotPlay,
+    #[error("RevealTooSoon")]
+    RevealTooSoon,
+    #[error("BetDisabled")]
+    BetDisabled,
+    #[error("WithdrawLocked")]
+    WithdrawLocked,
+    #[error("InvalidDenomination")]
+    InvalidDenomination,
+    #[error("ZeroBet")]
+    ZeroBet,
+    #[error("PayoutFailed")]
+    PayoutFailed,
+    #[error("VaultCapExceeded")]
+    VaultCapExceeded,
+    #[error("NoBets")]
+    NoBets,
+    #[error("RevealExpired")]
+    RevealExpired,
 }
 
 impl From<RouletteError> for ProgramError {
     fn from(e: RouletteError) -> Self {
         ProgramError::Custom(e as u32)
     }
-}
\ No newline at end of file
+}
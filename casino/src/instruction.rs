@@ -1,5 +1,27 @@
-use crate::state::RouletteBet;
+use crate::state::{
+    RouletteBet, ToleranceUnit, ZeroRule, MAX_DENOMINATIONS, MAX_SIGNERS, ORACLE_COUNT,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+// Minimum accounts each instruction takes, before any trailing signers
+// or oracle accounts
+pub const INITIALIZE_ACCOUNTS: usize = 4;
+pub const SAMPLE_ACCOUNTS: usize = 2;
+pub const INITIALIZE_HONEYPOT_ACCOUNTS: usize = 7;
+pub const INITIALIZE_AND_FUND_HONEYPOT_ACCOUNTS: usize = INITIALIZE_HONEYPOT_ACCOUNTS + 1;
+pub const WITHDRAW_FROM_HONEYPOT_ACCOUNTS: usize = 6;
+pub const ROULETTE_ACCOUNTS: usize = 9;
+pub const ROULETTE_FROM_WALLET_ACCOUNTS: usize = ROULETTE_ACCOUNTS + 1;
+pub const SYNC_VAULT_BALANCE_ACCOUNTS: usize = 2;
+pub const DEPOSIT_TO_HONEYPOT_ACCOUNTS: usize = 5;
+pub const MIGRATE_HONEYPOT_ACCOUNTS: usize = 3;
+// SetPaused, SetSigners, SetOracle, SetDenominations and RequestWithdraw
+pub const AUTHORITY_ACCOUNTS: usize = 2;
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
@@ -13,6 +35,26 @@ pub struct InitializeHoneypotArgs {
     pub tick_size: u64,
     pub max_bet_size: u64,
     pub minimum_bank_size: u64,
+    pub default_tolerance: u64,
+    pub min_reveal_delay: u64,
+    pub reload_threshold: u64,
+    pub restrict_owner_play: bool,
+    pub max_straight_bet: u64,
+    pub zero_rule: ZeroRule,
+    pub max_win: u64,
+    pub disabled_bets: u64,
+    pub withdraw_timelock_slots: u64,
+    pub max_distinct_bets: u8,
+    // 0 leaves the vault uncapped
+    pub max_vault_balance: u64,
+    pub tolerance_unit: ToleranceUnit,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeAndFundHoneypotArgs {
+    pub honeypot: InitializeHoneypotArgs,
+    pub initial_deposit: u64,
 }
 
 #[repr(C)]
@@ -21,6 +63,19 @@ pub struct WithdrawFromHoneypotArgs {
     pub amount_to_withdraw: u64,
 }
 
+// Withdraws everything above the honeypot's minimum_bank_size
+pub const WITHDRAW_ALL: u64 = u64::MAX;
+
+// A timelocked withdrawal lapses if not executed within this many slots of
+// unlocking (about a day), so a stale request cannot drain the bank later
+pub const WITHDRAW_WINDOW_SLOTS: u64 = 216_000;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct DepositToHoneypotArgs {
+    pub amount: u64,
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
 pub struct RouletteArgs {
@@ -28,6 +83,39 @@ pub struct RouletteArgs {
     pub bets: Vec<RouletteBet>,
 }
 
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct PreviewPayoutArgs {
+    pub bets: Vec<RouletteBet>,
+    pub outcome: u8,
+    pub zero_rule: ZeroRule,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetPausedArgs {
+    pub paused: bool,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetSignersArgs {
+    pub signers: [Pubkey; MAX_SIGNERS],
+    pub threshold: u8,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetOracleArgs {
+    pub oracles: [Pubkey; ORACLE_COUNT],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SetDenominationsArgs {
+    pub denominations: [u64; MAX_DENOMINATIONS],
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum RandomInstruction {
     Initialize,
@@ -35,4 +123,134 @@ pub enum RandomInstruction {
     InitializeHoneypot(InitializeHoneypotArgs),
     WithdrawFromHoneypot(WithdrawFromHoneypotArgs),
     Roulette(RouletteArgs),
+    PreviewPayout(PreviewPayoutArgs),
+    SetPaused(SetPausedArgs),
+    SetSigners(SetSignersArgs),
+    RouletteFromWallet(RouletteArgs),
+    SetOracle(SetOracleArgs),
+    RequestWithdraw(WithdrawFromHoneypotArgs),
+    InitializeAndFundHoneypot(InitializeAndFundHoneypotArgs),
+    SetDenominations(SetDenominationsArgs),
+    SyncVaultBalance,
+    DepositToHoneypot(DepositToHoneypotArgs),
+    MigrateHoneypot,
+}
+
+// `oracle_accounts` is the clock sysvar followed by the Pyth product/price pairs
+pub fn roulette_accounts(
+    rng: &Pubkey,
+    gambler: &Pubkey,
+    gambler_token_account: &Pubkey,
+    mint: &Pubkey,
+    honeypot: &Pubkey,
+    vault: &Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new(*rng, false),
+        AccountMeta::new(*gambler, true),
+        AccountMeta::new(*gambler_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(*honeypot, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(
+        oracle_accounts
+            .iter()
+            .map(|key| AccountMeta::new_readonly(*key, false)),
+    );
+    accounts
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn roulette(
+    program_id: &Pubkey,
+    rng: &Pubkey,
+    gambler: &Pubkey,
+    gambler_token_account: &Pubkey,
+    mint: &Pubkey,
+    honeypot: &Pubkey,
+    vault: &Pubkey,
+    oracle_accounts: &[Pubkey],
+    tolerance: u64,
+    bets: Vec<RouletteBet>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: roulette_accounts(
+            rng,
+            gambler,
+            gambler_token_account,
+            mint,
+            honeypot,
+            vault,
+            oracle_accounts,
+        ),
+        data: RandomInstruction::Roulette(RouletteArgs { tolerance, bets })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Bet;
+
+    // xorshift64, enough to spread bytes without pulling in a fuzzing crate
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Anything that decodes must re-encode to the same bytes, everything
+    // else must come back as an error rather than a panic
+    fn assert_decodes_cleanly(data: &[u8]) {
+        if let Ok(instruction) = RandomInstruction::try_from_slice(data) {
+            assert_eq!(instruction.try_to_vec().unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn random_bytes_never_panic() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..20_000 {
+            let len = (next(&mut state) % 160) as usize;
+            let mut data: Vec<u8> = (0..len).map(|_| next(&mut state) as u8).collect();
+            // Keep most tags in range so the argument decoders get exercised
+            if let Some(tag) = data.first_mut() {
+                *tag %= 18;
+            }
+            assert_decodes_cleanly(&data);
+        }
+    }
+
+    #[test]
+    fn truncated_and_oversized_bets_error() {
+        let bets = vec![
+            RouletteBet {
+                bet: Bet::Red,
+                amount: 3,
+            };
+            4
+        ];
+        let data = RandomInstruction::Roulette(RouletteArgs {
+            tolerance: 10,
+            bets,
+        })
+        .try_to_vec()
+        .unwrap();
+        assert_decodes_cleanly(&data);
+        for len in 0..data.len() {
+            assert!(RandomInstruction::try_from_slice(&data[..len]).is_err());
+        }
+        let mut oversized = data.clone();
+        oversized[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(RandomInstruction::try_from_slice(&oversized).is_err());
+    }
 }
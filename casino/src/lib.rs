@@ -1,9 +1,13 @@
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
+pub mod pda;
 pub mod processor;
+pub mod random_source;
 pub mod state;
 pub mod system_utils;
+#[cfg(test)]
+mod test_utils;
 pub mod validation_utils;
 // Export current sdk types for downstream users building with a different sdk version
 pub use solana_program;
@@ -0,0 +1,103 @@
+use solana_program::pubkey::Pubkey;
+
+pub const RNG_PREFIX: &[u8] = b"random";
+pub const HONEYPOT_PREFIX: &[u8] = b"honeypot";
+pub const VAULT_PREFIX: &[u8] = b"vault";
+
+pub struct HoneypotParams {
+    tick_size: [u8; 8],
+    max_bet_size: [u8; 8],
+    minimum_bank_size: [u8; 8],
+}
+
+impl HoneypotParams {
+    pub fn new(tick_size: u64, max_bet_size: u64, minimum_bank_size: u64) -> HoneypotParams {
+        HoneypotParams {
+            tick_size: tick_size.to_le_bytes(),
+            max_bet_size: max_bet_size.to_le_bytes(),
+            minimum_bank_size: minimum_bank_size.to_le_bytes(),
+        }
+    }
+}
+
+pub fn rng_seeds<'a>(payer: &'a Pubkey, program_id: &'a Pubkey) -> [&'a [u8]; 3] {
+    [RNG_PREFIX, payer.as_ref(), program_id.as_ref()]
+}
+
+pub fn honeypot_seeds<'a>(mint: &'a Pubkey, params: &'a HoneypotParams) -> [&'a [u8]; 5] {
+    [
+        HONEYPOT_PREFIX,
+        mint.as_ref(),
+        &params.tick_size,
+        &params.max_bet_size,
+        &params.minimum_bank_size,
+    ]
+}
+
+pub fn vault_seeds<'a>(mint: &'a Pubkey, params: &'a HoneypotParams) -> [&'a [u8]; 5] {
+    [
+        VAULT_PREFIX,
+        mint.as_ref(),
+        &params.tick_size,
+        &params.max_bet_size,
+        &params.minimum_bank_size,
+    ]
+}
+
+pub fn signer_seeds<'a>(seeds: &[&'a [u8]], bump: &'a [u8; 1]) -> Vec<&'a [u8]> {
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(bump);
+    signer_seeds
+}
+
+pub fn derive_rng_address(program_id: &Pubkey, payer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&rng_seeds(payer, program_id), program_id)
+}
+
+pub fn derive_honeypot_address(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    params: &HoneypotParams,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&honeypot_seeds(mint, params), program_id)
+}
+
+pub fn derive_vault_address(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    params: &HoneypotParams,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&vault_seeds(mint, params), program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn honeypot_and_vault_seeds_are_little_endian_params() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let params = HoneypotParams::new(10, 1_000, 5_000);
+        let raw = |prefix: &[u8]| {
+            Pubkey::find_program_address(
+                &[
+                    prefix,
+                    mint.as_ref(),
+                    &10u64.to_le_bytes(),
+                    &1_000u64.to_le_bytes(),
+                    &5_000u64.to_le_bytes(),
+                ],
+                &program_id,
+            )
+        };
+        assert_eq!(
+            derive_honeypot_address(&program_id, &mint, &params),
+            raw(HONEYPOT_PREFIX)
+        );
+        assert_eq!(
+            derive_vault_address(&program_id, &mint, &params),
+            raw(VAULT_PREFIX)
+        );
+    }
+}
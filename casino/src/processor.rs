@@ -1,24 +1,48 @@
-use crate::system_utils::create_or_allocate_account_raw;
+use crate::pda::{self, HoneypotParams};
+use crate::random_source::{PythRandomSource, RandomSource};
+use crate::system_utils::{create_or_allocate_account_raw, grow_account};
 use crate::validation_utils::{
-    assert_initialized, assert_is_ata, assert_keys_equal, assert_owned_by, assert_signer,
+    assert_account_count, assert_data_len, assert_initialized, assert_instructions_sysvar,
+    assert_is_ata, assert_is_initialized_ata, assert_keys_equal, assert_keys_unequal,
+    assert_not_frozen, assert_owned_by, assert_program_account, assert_signer, Versioned,
+};
+use crate::{
+    error::{RouletteError, UtilError},
+    instruction::{
+        InitializeHoneypotArgs, RandomInstruction, AUTHORITY_ACCOUNTS,
+        DEPOSIT_TO_HONEYPOT_ACCOUNTS, INITIALIZE_ACCOUNTS, INITIALIZE_AND_FUND_HONEYPOT_ACCOUNTS,
+        INITIALIZE_HONEYPOT_ACCOUNTS, MIGRATE_HONEYPOT_ACCOUNTS, ROULETTE_ACCOUNTS,
+        ROULETTE_FROM_WALLET_ACCOUNTS, SAMPLE_ACCOUNTS, SYNC_VAULT_BALANCE_ACCOUNTS, WITHDRAW_ALL,
+        WITHDRAW_FROM_HONEYPOT_ACCOUNTS, WITHDRAW_WINDOW_SLOTS,
+    },
+    state::{
+        max_potential_payout, BetResult, BetUnits, LowBankEvent, RouletteBet, RouletteResult,
+        Tokens, ToleranceUnit, ZeroRule, MAX_BETS, MAX_DENOMINATIONS, MAX_SIGNERS, ORACLE_COUNT,
+        POCKETS, STRAIGHT_MULTIPLIER,
+    },
 };
-use crate::{instruction::RandomInstruction, state::RouletteBet, error::RouletteError};
 use arrayref::array_refs;
 use borsh::{BorshDeserialize, BorshSerialize};
+use random::error::RandomError;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
+    epoch_schedule::EpochSchedule,
+    hash::{hashv, Hash},
+    log::sol_log_data,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
-    program_pack::Pack,
+    program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    serialize_utils::read_u16,
-    system_program, sysvar,
+    system_instruction, system_program,
+    sysvar::Sysvar,
 };
+use spl_associated_token_account::instruction::create_associated_token_account;
 use spl_token::{
-    instruction::{initialize_account, transfer},
-    state::Account,
+    instruction::{close_account, initialize_account, sync_native, transfer},
+    state::{Account, Mint},
 };
 
 #[repr(C)]
@@ -27,17 +51,34 @@ pub struct RNG {
     pub initialized: bool,
     pub value: u64,
     pub slot: u64,
+    // sha256(sample || slot || gambler) of the last spin
+    pub commitment: [u8; 32],
 }
 
 impl RNG {
-    pub const LEN: i64 = 1 + 8 + 8;
+    pub const LEN: usize = 1 + 8 + 8 + 32;
+    // Layout before the commitment field was added
+    pub const LEGACY_LEN: usize = 1 + 8 + 8;
 
     pub fn from_account_info(a: &AccountInfo) -> Result<RNG, ProgramError> {
+        assert_data_len(a, RNG::LEN)?;
         let rng = RNG::try_from_slice(&a.data.borrow())?;
         Ok(rng)
     }
+
+    // Reads a legacy account with a zeroed commitment
+    pub fn from_legacy_account_info(a: &AccountInfo) -> Result<RNG, ProgramError> {
+        assert_data_len(a, RNG::LEGACY_LEN)?;
+        let mut data = [0u8; RNG::LEN];
+        data[..RNG::LEGACY_LEN].copy_from_slice(&a.data.borrow()[..RNG::LEGACY_LEN]);
+        let rng = RNG::try_from_slice(&data)?;
+        Ok(rng)
+    }
 }
 
+// Bumped whenever fields are appended to Honeypot, see MigrateHoneypot
+pub const HONEYPOT_VERSION: u8 = 1;
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct Honeypot {
@@ -47,17 +88,137 @@ pub struct Honeypot {
     pub owner: Pubkey,
     pub mint: Pubkey,
     pub tick_size: u64,
-    pub max_bet_size: u64,
+    pub max_bet_size: Tokens,
     pub minimum_bank_size: u64,
+    pub default_tolerance: u64,
+    pub paused: bool,
+    pub min_reveal_delay: u64,
+    pub signers: [Pubkey; MAX_SIGNERS],
+    pub threshold: u8,
+    pub reload_threshold: u64,
+    pub oracles: [Pubkey; ORACLE_COUNT],
+    pub restrict_owner_play: bool,
+    pub max_straight_bet: Tokens,
+    pub zero_rule: ZeroRule,
+    pub max_win: Tokens,
+    pub disabled_bets: u64,
+    pub withdraw_timelock_slots: u64,
+    pub pending_withdrawal: u64,
+    pub withdraw_unlock_slot: u64,
+    pub allowed_denominations: [BetUnits; MAX_DENOMINATIONS],
+    pub max_distinct_bets: u8,
+    // Vault balance as of the last instruction that moved tokens through it
+    pub cached_vault_balance: u64,
+    pub max_vault_balance: u64,
+    // Unit of the default and per-spin oracle staleness tolerance
+    pub tolerance_unit: ToleranceUnit,
+    pub version: u8,
+}
+
+impl IsInitialized for Honeypot {
+    fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+}
+
+impl Versioned for Honeypot {
+    fn version(&self) -> u8 {
+        self.version
+    }
 }
 
 impl Honeypot {
-    pub const LEN: i64 = 1 + 1 + 1 + 32 + 32 + 8 + 8 + 8;
+    pub const LEN: usize = 1
+        + 1
+        + 1
+        + 32
+        + 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 8
+        + 32 * MAX_SIGNERS
+        + 1
+        + 8
+        + 32 * ORACLE_COUNT
+        + 1
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8 * MAX_DENOMINATIONS
+        + 1
+        + 8
+        + 8
+        + 1
+        + 1;
+
+    // Honeypots created before any configuration was stored: everything up
+    // to and including minimum_bank_size
+    pub const LEGACY_LEN: usize = 1 + 1 + 1 + 32 + 32 + 8 + 8 + 8;
+
+    pub fn assert_under_vault_cap(&self, vault_amount: u64, deposit: u64) -> ProgramResult {
+        if self.max_vault_balance == 0 {
+            return Ok(());
+        }
+        let new_balance = vault_amount
+            .checked_add(deposit)
+            .ok_or(RouletteError::NumericalOverflow)?;
+        if new_balance > self.max_vault_balance {
+            msg!(
+                "Deposit of {} would bring the vault to {}, above the cap of {}",
+                deposit,
+                new_balance,
+                self.max_vault_balance
+            );
+            return Err(RouletteError::VaultCapExceeded.into());
+        }
+        Ok(())
+    }
 
     pub fn from_account_info(a: &AccountInfo) -> Result<Honeypot, ProgramError> {
+        assert_data_len(a, Honeypot::LEN)?;
         let hp = Honeypot::try_from_slice(&a.data.borrow())?;
         Ok(hp)
     }
+
+    // With a zero threshold the owner alone has authority, otherwise at least
+    // `threshold` of the configured signers must also sign
+    pub fn assert_authority(
+        &self,
+        owner_info: &AccountInfo,
+        signer_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        assert_signer(owner_info)?;
+        assert_keys_equal(self.owner, *owner_info.key)?;
+        if self.threshold == 0 {
+            return Ok(());
+        }
+        let mut signed_keys = vec![*owner_info.key];
+        for signer_info in signer_infos.iter() {
+            assert_signer(signer_info)?;
+            signed_keys.push(*signer_info.key);
+        }
+        let approvals = self
+            .signers
+            .iter()
+            .filter(|signer| **signer != Pubkey::default() && signed_keys.contains(signer))
+            .count();
+        if approvals < self.threshold as usize {
+            msg!(
+                "{} of {} required signers approved",
+                approvals,
+                self.threshold
+            );
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
 }
 
 pub struct Processor;
@@ -66,6 +227,14 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         instruction_data: &[u8],
+    ) -> ProgramResult {
+        Self::process_with::<PythRandomSource>(program_id, accounts, instruction_data)
+    }
+
+    pub fn process_with<R: RandomSource>(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
     ) -> ProgramResult {
         let instruction = RandomInstruction::try_from_slice(instruction_data)?;
         match instruction {
@@ -75,20 +244,26 @@ impl Processor {
             }
             RandomInstruction::Sample(args) => {
                 msg!("Instruction 1: Sample");
-                sample(accounts, args.tolerance)
+                sample::<R>(program_id, accounts, args.tolerance)
             }
             RandomInstruction::InitializeHoneypot(args) => {
                 msg!("Instruction 2: InitializeHoneypot");
                 msg!("Tick size {}", args.tick_size);
                 msg!("Max bet size {}", args.max_bet_size);
                 msg!("Minimum bet size {}", args.minimum_bank_size);
-                initialize_honeypot(
-                    program_id,
-                    accounts,
-                    args.tick_size,
-                    args.max_bet_size,
-                    args.minimum_bank_size,
-                )
+                msg!("Default tolerance {}", args.default_tolerance);
+                msg!("Minimum reveal delay {}", args.min_reveal_delay);
+                msg!("Reload threshold {}", args.reload_threshold);
+                msg!("Restrict owner play {}", args.restrict_owner_play);
+                msg!("Max straight bet {}", args.max_straight_bet);
+                msg!("Zero rule {:?}", args.zero_rule);
+                msg!("Max win {}", args.max_win);
+                msg!("Disabled bets {:#x}", args.disabled_bets);
+                msg!("Withdraw timelock {}", args.withdraw_timelock_slots);
+                msg!("Max distinct bets {}", args.max_distinct_bets);
+                msg!("Max vault balance {}", args.max_vault_balance);
+                msg!("Tolerance unit {:?}", args.tolerance_unit);
+                initialize_honeypot(program_id, accounts, args)
             }
             RandomInstruction::WithdrawFromHoneypot(args) => {
                 msg!("Instruction 3: WithdrawFromHoneypot");
@@ -96,13 +271,64 @@ impl Processor {
             }
             RandomInstruction::Roulette(args) => {
                 msg!("Instruction 4: Roulette");
-                roulette(program_id, accounts, args.tolerance, args.bets)
+                roulette::<R>(program_id, accounts, args.tolerance, args.bets)
+            }
+            RandomInstruction::PreviewPayout(args) => {
+                msg!("Instruction 5: PreviewPayout");
+                preview_payout(args.bets, args.outcome, args.zero_rule)
+            }
+            RandomInstruction::SetPaused(args) => {
+                msg!("Instruction 6: SetPaused");
+                set_paused(program_id, accounts, args.paused)
+            }
+            RandomInstruction::SetSigners(args) => {
+                msg!("Instruction 7: SetSigners");
+                set_signers(program_id, accounts, args.signers, args.threshold)
+            }
+            RandomInstruction::RouletteFromWallet(args) => {
+                msg!("Instruction 8: RouletteFromWallet");
+                roulette_from_wallet::<R>(program_id, accounts, args.tolerance, args.bets)
+            }
+            RandomInstruction::SetOracle(args) => {
+                msg!("Instruction 9: SetOracle");
+                set_oracle(program_id, accounts, args.oracles)
+            }
+            RandomInstruction::RequestWithdraw(args) => {
+                msg!("Instruction 10: RequestWithdraw");
+                request_withdraw(program_id, accounts, args.amount_to_withdraw)
+            }
+            RandomInstruction::InitializeAndFundHoneypot(args) => {
+                msg!("Instruction 11: InitializeAndFundHoneypot");
+                msg!("Initial deposit {}", args.initial_deposit);
+                initialize_and_fund_honeypot(
+                    program_id,
+                    accounts,
+                    args.honeypot,
+                    args.initial_deposit,
+                )
+            }
+            RandomInstruction::SetDenominations(args) => {
+                msg!("Instruction 12: SetDenominations");
+                set_denominations(program_id, accounts, args.denominations)
+            }
+            RandomInstruction::SyncVaultBalance => {
+                msg!("Instruction 13: SyncVaultBalance");
+                sync_vault_balance(program_id, accounts)
+            }
+            RandomInstruction::DepositToHoneypot(args) => {
+                msg!("Instruction 14: DepositToHoneypot");
+                deposit_to_honeypot(program_id, accounts, args.amount)
+            }
+            RandomInstruction::MigrateHoneypot => {
+                msg!("Instruction 15: MigrateHoneypot");
+                migrate_honeypot(program_id, accounts)
             }
         }
     }
 }
 
 fn initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, INITIALIZE_ACCOUNTS)?;
     let account_info_iter = &mut accounts.iter();
     let rng_account_info = next_account_info(account_info_iter)?;
     let payer_info = next_account_info(account_info_iter)?;
@@ -110,21 +336,15 @@ fn initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let system_program_info = next_account_info(account_info_iter)?;
     if !rng_account_info.data_is_empty() {
         msg!("Received nonempty account");
-        return Err(ProgramError::AccountAlreadyInitialized.into());
+        return Err(ProgramError::AccountAlreadyInitialized);
     }
-    let (rng_key, rng_bump_seed) = Pubkey::find_program_address(
-        &[b"random", payer_info.key.as_ref(), program_id.as_ref()],
-        program_id,
-    );
-    let rng_seeds = &[
-        b"random",
-        payer_info.key.as_ref(),
-        program_id.as_ref(),
-        &[rng_bump_seed],
-    ];
+    let (rng_key, rng_bump_seed) = pda::derive_rng_address(program_id, payer_info.key);
+    let rng_bump_seed = [rng_bump_seed];
+    let rng_seeds =
+        &pda::signer_seeds(&pda::rng_seeds(payer_info.key, program_id), &rng_bump_seed)[..];
     if rng_key != *rng_account_info.key {
         msg!("RNG account doesn't match");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
     create_or_allocate_account_raw(
         rng_account_info,
@@ -132,35 +352,116 @@ fn initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         system_program_info,
         payer_info,
         program_id,
-        RNG::LEN as usize,
+        RNG::LEN,
         rng_seeds,
     )?;
     Ok(())
 }
 
-fn sample(accounts: &[AccountInfo], tolerance: u64) -> ProgramResult {
-    let (rng_accounts, remaining_accounts) = array_refs![accounts, 1; .. ;];
-    let (random_sample, slot) = random::random::sample(remaining_accounts, tolerance)?;
+fn sample<R: RandomSource>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tolerance: u64,
+) -> ProgramResult {
+    assert_account_count(accounts, SAMPLE_ACCOUNTS)?;
+    let (rng_accounts, remaining_accounts) = array_refs![accounts, SAMPLE_ACCOUNTS; .. ;];
     let account_info_iter = &mut rng_accounts.iter();
     let rng_info = next_account_info(account_info_iter)?;
-    let mut rng = RNG::from_account_info(rng_info)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    assert_owned_by(rng_info, program_id)?;
+    // Sample moves rng.slot, which min_reveal_delay counts from, so only the
+    // RNG's owner may call it
+    assert_signer(payer_info)?;
+    let (rng_key, _) = pda::derive_rng_address(program_id, payer_info.key);
+    assert_keys_equal(rng_key, *rng_info.key)?;
+    let (random_sample, slot) = R::sample(remaining_accounts, tolerance)?;
+    // Sample takes no system program to grow legacy accounts with, so they
+    // keep their layout until the next spin grows them
+    let legacy = rng_info.data_len() < RNG::LEN;
+    let mut rng = if legacy {
+        RNG::from_legacy_account_info(rng_info)?
+    } else {
+        RNG::from_account_info(rng_info)?
+    };
     if !rng.initialized {
         rng.initialized = true;
     }
     rng.value = random_sample;
     rng.slot = slot;
     msg!("Sample {}", random_sample);
-    rng.serialize(&mut *rng_info.data.borrow_mut())?;
+    if legacy {
+        let data = rng.try_to_vec()?;
+        rng_info.data.borrow_mut()[..RNG::LEGACY_LEN].copy_from_slice(&data[..RNG::LEGACY_LEN]);
+    } else {
+        rng.serialize(&mut *rng_info.data.borrow_mut())?;
+    }
     Ok(())
 }
 
 fn initialize_honeypot(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    tick_size: u64,
-    max_bet_size: u64,
-    minimum_bank_size: u64,
+    args: InitializeHoneypotArgs,
 ) -> ProgramResult {
+    assert_account_count(accounts, INITIALIZE_HONEYPOT_ACCOUNTS)?;
+    let InitializeHoneypotArgs {
+        tick_size,
+        max_bet_size,
+        minimum_bank_size,
+        default_tolerance,
+        min_reveal_delay,
+        reload_threshold,
+        restrict_owner_play,
+        max_straight_bet,
+        zero_rule,
+        max_win,
+        disabled_bets,
+        withdraw_timelock_slots,
+        max_distinct_bets,
+        max_vault_balance,
+        tolerance_unit,
+    } = args;
+    if tick_size == 0 {
+        msg!("Tick size must be non-zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if max_bet_size % tick_size != 0 {
+        msg!(
+            "Max bet size {} must be a multiple of the tick size {}",
+            max_bet_size,
+            tick_size
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if min_reveal_delay == 0 {
+        msg!("Minimum reveal delay must be at least 1 slot");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if default_tolerance == 0 {
+        msg!("Default tolerance must be non-zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // A capped vault must be able to sit above the floor while still covering
+    // the worst-case payout of a single max bet, or no max bet can ever settle
+    if max_vault_balance != 0 {
+        let mut worst_payout = max_bet_size.saturating_mul(STRAIGHT_MULTIPLIER);
+        if max_win != 0 {
+            worst_payout = worst_payout.min(max_win);
+        }
+        let playable = max_vault_balance
+            .checked_sub(minimum_bank_size)
+            .filter(|room| *room > 0)
+            .is_some_and(|room| room.saturating_add(max_bet_size) >= worst_payout);
+        if !playable {
+            msg!(
+                "Vault cap {} above the {} floor cannot cover a {} payout",
+                max_vault_balance,
+                minimum_bank_size,
+                worst_payout
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
     let account_info_iter = &mut accounts.iter();
     let honeypot_info = next_account_info(account_info_iter)?;
     let mint_info = next_account_info(account_info_iter)?;
@@ -174,50 +475,25 @@ fn initialize_honeypot(
     assert_keys_equal(spl_token::id(), *token_program_info.key)?;
     msg!("Checking proper mint");
     assert_owned_by(mint_info, token_program_info.key)?;
-    let (honeypot_key, honeypot_bump_seed) = Pubkey::find_program_address(
-        &[
-            b"honeypot",
-            mint_info.key.as_ref(),
-            &tick_size.to_le_bytes(),
-            &max_bet_size.to_le_bytes(),
-            &minimum_bank_size.to_le_bytes(),
-        ],
-        program_id,
-    );
-    let honeypot_seeds = &[
-        b"honeypot",
-        mint_info.key.as_ref(),
-        &tick_size.to_le_bytes(),
-        &max_bet_size.to_le_bytes(),
-        &minimum_bank_size.to_le_bytes(),
-        &[honeypot_bump_seed],
-    ];
-    let (vault_key, vault_bump_seed) = Pubkey::find_program_address(
-        &[
-            b"vault",
-            mint_info.key.as_ref(),
-            &tick_size.to_le_bytes(),
-            &max_bet_size.to_le_bytes(),
-            &minimum_bank_size.to_le_bytes(),
-        ],
-        program_id,
-    );
+    let params = HoneypotParams::new(tick_size, max_bet_size, minimum_bank_size);
+    let (honeypot_key, honeypot_bump_seed) =
+        pda::derive_honeypot_address(program_id, mint_info.key, &params);
+    let honeypot_bump = [honeypot_bump_seed];
+    let honeypot_seeds =
+        &pda::signer_seeds(&pda::honeypot_seeds(mint_info.key, &params), &honeypot_bump)[..];
+    let (vault_key, vault_bump_seed) =
+        pda::derive_vault_address(program_id, mint_info.key, &params);
     msg!("Vault {}: ", vault_key);
-    let vault_seeds = &[
-        b"vault",
-        mint_info.key.as_ref(),
-        &tick_size.to_le_bytes(),
-        &max_bet_size.to_le_bytes(),
-        &minimum_bank_size.to_le_bytes(),
-        &[vault_bump_seed],
-    ];
+    let vault_bump = [vault_bump_seed];
+    let vault_seeds =
+        &pda::signer_seeds(&pda::vault_seeds(mint_info.key, &params), &vault_bump)[..];
     create_or_allocate_account_raw(
         honeypot_info,
         rent_sysvar_info,
         system_program_info,
         owner_info,
         program_id,
-        Honeypot::LEN as usize,
+        Honeypot::LEN,
         honeypot_seeds,
     )?;
     create_or_allocate_account_raw(
@@ -253,8 +529,73 @@ fn initialize_honeypot(
     honeypot.owner = *owner_info.key;
     honeypot.mint = *mint_info.key;
     honeypot.tick_size = tick_size;
-    honeypot.max_bet_size = max_bet_size;
+    honeypot.max_bet_size = Tokens::new(max_bet_size);
     honeypot.minimum_bank_size = minimum_bank_size;
+    honeypot.default_tolerance = default_tolerance;
+    honeypot.min_reveal_delay = min_reveal_delay;
+    honeypot.reload_threshold = reload_threshold;
+    honeypot.restrict_owner_play = restrict_owner_play;
+    honeypot.max_straight_bet = Tokens::new(max_straight_bet);
+    honeypot.zero_rule = zero_rule;
+    honeypot.max_win = Tokens::new(max_win);
+    honeypot.disabled_bets = disabled_bets;
+    honeypot.withdraw_timelock_slots = withdraw_timelock_slots;
+    honeypot.max_distinct_bets = max_distinct_bets;
+    honeypot.max_vault_balance = max_vault_balance;
+    honeypot.tolerance_unit = tolerance_unit;
+    honeypot.version = HONEYPOT_VERSION;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn initialize_and_fund_honeypot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: InitializeHoneypotArgs,
+    initial_deposit: u64,
+) -> ProgramResult {
+    assert_account_count(accounts, INITIALIZE_AND_FUND_HONEYPOT_ACCOUNTS)?;
+    // The table only accepts bets while the vault holds more than the floor
+    if initial_deposit <= args.minimum_bank_size {
+        msg!(
+            "Initial deposit {} must exceed the minimum bank size {}",
+            initial_deposit,
+            args.minimum_bank_size
+        );
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let (honeypot_accounts, remaining_accounts) =
+        array_refs![accounts, INITIALIZE_HONEYPOT_ACCOUNTS; .. ;];
+    initialize_honeypot(program_id, honeypot_accounts, args)?;
+    let account_info_iter = &mut honeypot_accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let owner_token_account_info = next_account_info(&mut remaining_accounts.iter())?;
+    assert_signer(owner_info)?;
+    assert_is_ata(owner_token_account_info, owner_info.key, mint_info.key)?;
+    let mut honeypot = Honeypot::from_account_info(honeypot_info)?;
+    honeypot.assert_under_vault_cap(0, initial_deposit)?;
+    invoke(
+        &transfer(
+            token_program_info.key,
+            owner_token_account_info.key,
+            vault_info.key,
+            owner_info.key,
+            &[],
+            initial_deposit,
+        )?,
+        &[
+            owner_token_account_info.clone(),
+            vault_info.clone(),
+            owner_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    let vault: Account = assert_initialized(vault_info)?;
+    honeypot.cached_vault_balance = vault.amount;
     honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
     Ok(())
 }
@@ -264,6 +605,7 @@ fn withdraw_from_honeypot(
     accounts: &[AccountInfo],
     amount_to_withdraw: u64,
 ) -> ProgramResult {
+    assert_account_count(accounts, WITHDRAW_FROM_HONEYPOT_ACCOUNTS)?;
     let account_info_iter = &mut accounts.iter();
     let honeypot_info = next_account_info(account_info_iter)?;
     let vault_info = next_account_info(account_info_iter)?;
@@ -271,34 +613,73 @@ fn withdraw_from_honeypot(
     let owner_info = next_account_info(account_info_iter)?;
     let owner_token_account_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
-    let honeypot = Honeypot::from_account_info(honeypot_info)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
     assert_is_ata(owner_token_account_info, owner_info.key, mint_info.key)?;
-    assert_signer(owner_info)?;
-    assert_owned_by(honeypot_info, program_id)?;
     assert_owned_by(mint_info, token_program_info.key)?;
     assert_keys_equal(spl_token::id(), *token_program_info.key)?;
-    assert_keys_equal(honeypot.owner, *owner_info.key)?;
+    let _mint: Mint = assert_initialized(mint_info)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
     assert_keys_equal(honeypot.mint, *mint_info.key)?;
-    let honeypot_seeds = &[
-        b"honeypot",
-        mint_info.key.as_ref(),
-        &honeypot.tick_size.to_le_bytes(),
-        &honeypot.max_bet_size.to_le_bytes(),
-        &honeypot.minimum_bank_size.to_le_bytes(),
-        &[honeypot.honeypot_bump_seed],
-    ];
-    let vault_seeds = &[
-        b"vault",
-        mint_info.key.as_ref(),
-        &honeypot.tick_size.to_le_bytes(),
-        &honeypot.max_bet_size.to_le_bytes(),
-        &honeypot.minimum_bank_size.to_le_bytes(),
-        &[honeypot.vault_bump_seed],
-    ];
-    let honeypot_key = Pubkey::create_program_address(honeypot_seeds, program_id).unwrap();
-    let vault_key = Pubkey::create_program_address(vault_seeds, program_id).unwrap();
+    let params = HoneypotParams::new(
+        honeypot.tick_size,
+        honeypot.max_bet_size.get(),
+        honeypot.minimum_bank_size,
+    );
+    let honeypot_bump_seed = [honeypot.honeypot_bump_seed];
+    let vault_bump_seed = [honeypot.vault_bump_seed];
+    let honeypot_seeds = &pda::signer_seeds(
+        &pda::honeypot_seeds(mint_info.key, &params),
+        &honeypot_bump_seed,
+    )[..];
+    let vault_seeds =
+        &pda::signer_seeds(&pda::vault_seeds(mint_info.key, &params), &vault_bump_seed)[..];
+    let honeypot_key = Pubkey::create_program_address(honeypot_seeds, program_id)?;
+    let vault_key = Pubkey::create_program_address(vault_seeds, program_id)?;
     assert_keys_equal(honeypot_key, *honeypot_info.key)?;
     assert_keys_equal(vault_key, *vault_info.key)?;
+    let vault: Account = assert_initialized(vault_info)?;
+    assert_keys_equal(vault.owner, *honeypot_info.key)?;
+    // Timelocked tables only pay out a withdrawal requested at least
+    // withdraw_timelock_slots earlier, giving players notice before the bank shrinks
+    if honeypot.withdraw_timelock_slots > 0 {
+        let clock = Clock::get()?;
+        if honeypot.pending_withdrawal == 0 || amount_to_withdraw != honeypot.pending_withdrawal {
+            msg!(
+                "Withdrawal of {} was not requested, pending request is {}",
+                amount_to_withdraw,
+                honeypot.pending_withdrawal
+            );
+            return Err(RouletteError::WithdrawLocked.into());
+        }
+        if clock.slot < honeypot.withdraw_unlock_slot {
+            msg!(
+                "Withdrawal unlocks at slot {}, current slot {}",
+                honeypot.withdraw_unlock_slot,
+                clock.slot
+            );
+            return Err(RouletteError::WithdrawLocked.into());
+        }
+        let expiry_slot = honeypot
+            .withdraw_unlock_slot
+            .saturating_add(WITHDRAW_WINDOW_SLOTS);
+        if clock.slot > expiry_slot {
+            msg!(
+                "Withdrawal request lapsed at slot {}, current slot {}, request it again",
+                expiry_slot,
+                clock.slot
+            );
+            return Err(RouletteError::WithdrawLocked.into());
+        }
+        honeypot.pending_withdrawal = 0;
+        honeypot.withdraw_unlock_slot = 0;
+    }
+    let amount_to_withdraw = if amount_to_withdraw == WITHDRAW_ALL {
+        vault.amount.saturating_sub(honeypot.minimum_bank_size)
+    } else {
+        amount_to_withdraw
+    };
+    msg!("Withdrawing {} tokens", amount_to_withdraw);
     invoke_signed(
         &transfer(
             token_program_info.key,
@@ -316,18 +697,319 @@ fn withdraw_from_honeypot(
         ],
         &[honeypot_seeds],
     )?;
+    let vault: Account = assert_initialized(vault_info)?;
+    honeypot.cached_vault_balance = vault.amount;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
     Ok(())
 }
 
-fn roulette(
+fn deposit_to_honeypot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    assert_account_count(accounts, DEPOSIT_TO_HONEYPOT_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let depositor_info = next_account_info(account_info_iter)?;
+    let depositor_token_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_signer(depositor_info)?;
+    assert_keys_equal(spl_token::id(), *token_program_info.key)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    let params = HoneypotParams::new(
+        honeypot.tick_size,
+        honeypot.max_bet_size.get(),
+        honeypot.minimum_bank_size,
+    );
+    let vault_bump_seed = [honeypot.vault_bump_seed];
+    let vault_seeds =
+        &pda::signer_seeds(&pda::vault_seeds(&honeypot.mint, &params), &vault_bump_seed)[..];
+    let vault_key = Pubkey::create_program_address(vault_seeds, program_id)?;
+    assert_keys_equal(vault_key, *vault_info.key)?;
+    let vault: Account = assert_initialized(vault_info)?;
+    honeypot.assert_under_vault_cap(vault.amount, amount)?;
+    msg!("Depositing {}", amount);
+    invoke(
+        &transfer(
+            token_program_info.key,
+            depositor_token_account_info.key,
+            vault_info.key,
+            depositor_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            depositor_token_account_info.clone(),
+            vault_info.clone(),
+            depositor_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    let vault: Account = assert_initialized(vault_info)?;
+    honeypot.cached_vault_balance = vault.amount;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+// Permissionless: grows a honeypot created with the legacy layout to the
+// current one. Appended fields start zeroed, which leaves every limit off,
+// apart from the reveal delay which keeps the original one slot minimum
+fn migrate_honeypot(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, MIGRATE_HONEYPOT_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    assert_signer(payer_info)?;
+    assert_keys_equal(system_program::id(), *system_program_info.key)?;
+    assert_owned_by(honeypot_info, program_id)?;
+    if honeypot_info.data_len() != Honeypot::LEGACY_LEN {
+        msg!(
+            "Honeypot holds {} bytes, only {} byte honeypots can be migrated",
+            honeypot_info.data_len(),
+            Honeypot::LEGACY_LEN
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    msg!("Growing honeypot to {} bytes", Honeypot::LEN);
+    grow_account(
+        honeypot_info,
+        payer_info,
+        system_program_info,
+        Honeypot::LEN,
+    )?;
+    // The appended fields, version included, are still zeroed
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, 0)?;
+    let params = HoneypotParams::new(
+        honeypot.tick_size,
+        honeypot.max_bet_size.get(),
+        honeypot.minimum_bank_size,
+    );
+    let honeypot_bump_seed = [honeypot.honeypot_bump_seed];
+    let honeypot_seeds = &pda::signer_seeds(
+        &pda::honeypot_seeds(&honeypot.mint, &params),
+        &honeypot_bump_seed,
+    )[..];
+    let honeypot_key = Pubkey::create_program_address(honeypot_seeds, program_id)?;
+    assert_keys_equal(honeypot_key, *honeypot_info.key)?;
+    honeypot.min_reveal_delay = 1;
+    honeypot.version = HONEYPOT_VERSION;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+// Permissionless: refreshes the cached balance after tokens reach the vault
+// outside of the program, e.g. a direct transfer
+fn sync_vault_balance(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, SYNC_VAULT_BALANCE_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    let params = HoneypotParams::new(
+        honeypot.tick_size,
+        honeypot.max_bet_size.get(),
+        honeypot.minimum_bank_size,
+    );
+    let vault_bump_seed = [honeypot.vault_bump_seed];
+    let vault_seeds =
+        &pda::signer_seeds(&pda::vault_seeds(&honeypot.mint, &params), &vault_bump_seed)[..];
+    let vault_key = Pubkey::create_program_address(vault_seeds, program_id)?;
+    assert_keys_equal(vault_key, *vault_info.key)?;
+    let vault: Account = assert_initialized(vault_info)?;
+    msg!(
+        "Vault balance {} (cached {})",
+        vault.amount,
+        honeypot.cached_vault_balance
+    );
+    honeypot.cached_vault_balance = vault.amount;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn request_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_to_withdraw: u64,
+) -> ProgramResult {
+    assert_account_count(accounts, AUTHORITY_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
+    let clock = Clock::get()?;
+    honeypot.pending_withdrawal = amount_to_withdraw;
+    honeypot.withdraw_unlock_slot = clock.slot.saturating_add(honeypot.withdraw_timelock_slots);
+    msg!(
+        "Withdrawal of {} unlocks at slot {}",
+        amount_to_withdraw,
+        honeypot.withdraw_unlock_slot
+    );
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    assert_account_count(accounts, AUTHORITY_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
+    msg!("Paused {}", paused);
+    honeypot.paused = paused;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn set_signers(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    signers: [Pubkey; MAX_SIGNERS],
+    threshold: u8,
+) -> ProgramResult {
+    assert_account_count(accounts, AUTHORITY_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
+    let mut configured: Vec<Pubkey> = vec![];
+    for signer in signers
+        .iter()
+        .filter(|signer| **signer != Pubkey::default())
+    {
+        if configured.contains(signer) {
+            return Err(UtilError::PublicKeysShouldBeUnique.into());
+        }
+        configured.push(*signer);
+    }
+    if threshold as usize > configured.len() {
+        msg!(
+            "Threshold {} exceeds the {} configured signers",
+            threshold,
+            configured.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    msg!("Threshold {} of {}", threshold, configured.len());
+    honeypot.signers = signers;
+    honeypot.threshold = threshold;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn set_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    oracles: [Pubkey; ORACLE_COUNT],
+) -> ProgramResult {
+    assert_account_count(accounts, AUTHORITY_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
+    for oracle in oracles.iter() {
+        msg!("Oracle {}", oracle);
+    }
+    honeypot.oracles = oracles;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn set_denominations(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    denominations: [u64; MAX_DENOMINATIONS],
+) -> ProgramResult {
+    assert_account_count(accounts, AUTHORITY_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let honeypot_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    honeypot.assert_authority(owner_info, account_info_iter.as_slice())?;
+    msg!("Denominations {:?}", denominations);
+    honeypot.allowed_denominations = denominations.map(BetUnits::new);
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    Ok(())
+}
+
+fn roulette_from_wallet<R: RandomSource>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tolerance: u64,
+    bets: Vec<RouletteBet>,
+) -> ProgramResult {
+    assert_account_count(accounts, ROULETTE_FROM_WALLET_ACCOUNTS)?;
+    let account_info_iter = &mut accounts.iter();
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+    let roulette_accounts = account_info_iter.as_slice();
+    let _rng_info = next_account_info(account_info_iter)?;
+    let gambler_info = next_account_info(account_info_iter)?;
+    let gambler_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let _honeypot_info = next_account_info(account_info_iter)?;
+    let _vault_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let _instruction_sysvar_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    assert_keys_equal(
+        spl_associated_token_account::id(),
+        *associated_token_program_info.key,
+    )?;
+    assert_keys_equal(spl_token::id(), *token_program_info.key)?;
+    assert_keys_equal(system_program::id(), *system_program_info.key)?;
+    if gambler_token_account_info.data_is_empty() {
+        msg!("Creating gambler token account");
+        invoke(
+            &create_associated_token_account(
+                gambler_info.key,
+                gambler_info.key,
+                mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                gambler_info.clone(),
+                gambler_token_account_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+    }
+    roulette::<R>(program_id, roulette_accounts, tolerance, bets)
+}
+
+fn roulette<R: RandomSource>(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     tolerance: u64,
     bets: Vec<RouletteBet>,
 ) -> ProgramResult {
     msg!("Starting Roulette spin");
-    let (main_accounts, oracle_accounts) = array_refs![accounts, 8; .. ;];
-    let (random_sample, slot) = random::random::sample(oracle_accounts, tolerance)?;
+    if bets.is_empty() {
+        msg!("A spin needs at least one bet");
+        return Err(RouletteError::NoBets.into());
+    }
+    if bets.len() > MAX_BETS {
+        msg!("Cannot place more than {} bets in a single spin", MAX_BETS);
+        return Err(RouletteError::TooManyBets.into());
+    }
+    assert_account_count(accounts, ROULETTE_ACCOUNTS)?;
+    let (main_accounts, oracle_accounts) = array_refs![accounts, ROULETTE_ACCOUNTS; .. ;];
     let account_info_iter = &mut main_accounts.iter();
     let rng_info = next_account_info(account_info_iter)?;
     let gambler_info = next_account_info(account_info_iter)?;
@@ -337,91 +1019,282 @@ fn roulette(
     let vault_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
     let instruction_sysvar_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
     assert_keys_equal(spl_token::id(), *token_program_info.key)?;
+    assert_keys_equal(system_program::id(), *system_program_info.key)?;
     msg!("ATA check");
-    assert_is_ata(gambler_token_account_info, gambler_info.key, mint_info.key)?;
-    assert_owned_by(honeypot_info, program_id)?;
-    if *instruction_sysvar_account_info.key != sysvar::instructions::id() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let instruction_sysvar = instruction_sysvar_account_info.data.borrow();
-    let current_instruction = sysvar::instructions::load_current_index(&instruction_sysvar);
-    let mut idx = 0;
-    let num_instructions =
-        read_u16(&mut idx, &instruction_sysvar).map_err(|_| ProgramError::InvalidAccountData)?;
+    let gambler_token_account =
+        assert_is_initialized_ata(gambler_token_account_info, gambler_info.key, mint_info.key)?;
+    assert_owned_by(rng_info, program_id)?;
+    let (current_instruction, num_instructions) =
+        assert_instructions_sysvar(instruction_sysvar_account_info)?;
     msg!(
         "current_ix: {}, num_ix: {}",
         current_instruction,
         num_instructions
     );
-    if current_instruction < num_instructions - 1{
+    if current_instruction + 1 < num_instructions {
         msg!("This must be the last instruction in the transaction");
         return Err(RouletteError::SuspiciousTransaction.into());
     }
     assert_signer(gambler_info)?;
+    // The RNG must be the gambler's own PDA, otherwise a player could spin
+    // against someone else's sample
+    let (rng_key, _) = pda::derive_rng_address(program_id, gambler_info.key);
+    assert_keys_equal(rng_key, *rng_info.key)?;
+    let distinct_keys = [
+        rng_info.key,
+        gambler_info.key,
+        gambler_token_account_info.key,
+        honeypot_info.key,
+        vault_info.key,
+    ];
+    for (i, key) in distinct_keys.iter().enumerate() {
+        for other in distinct_keys[i + 1..].iter() {
+            assert_keys_unequal(**key, **other)?;
+        }
+    }
+    // RNG accounts created before the commitment field was added are grown
+    // on their next spin
+    if rng_info.data_len() < RNG::LEN {
+        msg!("Growing RNG account to {} bytes", RNG::LEN);
+        grow_account(rng_info, gambler_info, system_program_info, RNG::LEN)?;
+    }
     let mut rng = RNG::from_account_info(rng_info)?;
-    let honeypot = Honeypot::from_account_info(honeypot_info)?;
+    let mut honeypot: Honeypot =
+        assert_program_account(honeypot_info, program_id, Honeypot::LEN, HONEYPOT_VERSION)?;
+    assert_not_frozen(gambler_token_account_info)?;
+    assert_not_frozen(vault_info)?;
     let vault: Account = assert_initialized(vault_info)?;
-    let honeypot_seeds = &[
-        b"honeypot",
-        mint_info.key.as_ref(),
-        &honeypot.tick_size.to_le_bytes(),
-        &honeypot.max_bet_size.to_le_bytes(),
-        &honeypot.minimum_bank_size.to_le_bytes(),
-        &[honeypot.honeypot_bump_seed],
-    ];
-    let vault_seeds = &[
-        b"vault",
-        mint_info.key.as_ref(),
-        &honeypot.tick_size.to_le_bytes(),
-        &honeypot.max_bet_size.to_le_bytes(),
-        &honeypot.minimum_bank_size.to_le_bytes(),
-        &[honeypot.vault_bump_seed],
-    ];
-    let honeypot_key = Pubkey::create_program_address(honeypot_seeds, program_id).unwrap();
-    let vault_key = Pubkey::create_program_address(vault_seeds, program_id).unwrap();
+    let params = HoneypotParams::new(
+        honeypot.tick_size,
+        honeypot.max_bet_size.get(),
+        honeypot.minimum_bank_size,
+    );
+    let honeypot_bump_seed = [honeypot.honeypot_bump_seed];
+    let vault_bump_seed = [honeypot.vault_bump_seed];
+    let honeypot_seeds = &pda::signer_seeds(
+        &pda::honeypot_seeds(mint_info.key, &params),
+        &honeypot_bump_seed,
+    )[..];
+    let vault_seeds =
+        &pda::signer_seeds(&pda::vault_seeds(mint_info.key, &params), &vault_bump_seed)[..];
+    let honeypot_key = Pubkey::create_program_address(honeypot_seeds, program_id)?;
+    let vault_key = Pubkey::create_program_address(vault_seeds, program_id)?;
     assert_keys_equal(honeypot_key, *honeypot_info.key)?;
     assert_keys_equal(vault_key, *vault_info.key)?;
+    assert_keys_equal(vault.owner, *honeypot_info.key)?;
+    if honeypot.paused {
+        msg!("Honeypot is paused");
+        return Err(RouletteError::Paused.into());
+    }
+    if honeypot.restrict_owner_play && *gambler_info.key == honeypot.owner {
+        msg!("The honeypot owner cannot play against their own table");
+        return Err(RouletteError::OwnerCannotPlay.into());
+    }
+    let mut placed_bets: u64 = 0;
+    for bet in bets.iter() {
+        bet.validate()?;
+        placed_bets |= bet.bet.mask();
+        if honeypot.disabled_bets & bet.bet.mask() != 0 {
+            msg!("Bet {:?} is disabled at this table", bet.bet);
+            return Err(RouletteError::BetDisabled.into());
+        }
+        // With no denominations configured any bet size is accepted
+        if honeypot.allowed_denominations.iter().any(|d| d.get() != 0)
+            && !honeypot.allowed_denominations.contains(&bet.units())
+        {
+            msg!("Bet size {} is not an allowed denomination", bet.amount);
+            return Err(RouletteError::InvalidDenomination.into());
+        }
+    }
+    if honeypot.max_distinct_bets > 0
+        && placed_bets.count_ones() > honeypot.max_distinct_bets as u32
+    {
+        msg!(
+            "{} distinct bets placed, at most {} allowed",
+            placed_bets.count_ones(),
+            honeypot.max_distinct_bets
+        );
+        return Err(RouletteError::TooManyBets.into());
+    }
+    // Scaling the worst case by tick_size up front means no outcome can
+    // overflow the payout later, and bets that could win more than max_win
+    // are refused before the wheel is sampled, so no outcome is paid short
+    let max_payout =
+        max_potential_payout(&bets, honeypot.zero_rule)?.to_tokens(honeypot.tick_size)?;
+    if honeypot.max_win.get() > 0 && max_payout > honeypot.max_win {
+        msg!(
+            "Bets could win {} tokens, above the {} token limit",
+            max_payout.get(),
+            honeypot.max_win.get()
+        );
+        return Err(RouletteError::AmountTooLarge.into());
+    }
     msg!("Validation checks passed");
+    // Zero asks for the table's default, which is also the loosest tolerance
+    // a gambler may request
+    let tolerance = if tolerance == 0 {
+        honeypot.default_tolerance
+    } else {
+        tolerance.min(honeypot.default_tolerance)
+    };
+    // Only a tolerance in seconds needs the sysvars to convert it
+    let tolerance = match honeypot.tolerance_unit {
+        ToleranceUnit::Slots => tolerance,
+        ToleranceUnit::Seconds => {
+            let clock = Clock::get()?;
+            let epoch_start_slot = EpochSchedule::get()?.get_first_slot_in_epoch(clock.epoch);
+            ToleranceUnit::Seconds.to_slots(
+                tolerance,
+                clock.slot,
+                clock.unix_timestamp,
+                epoch_start_slot,
+                clock.epoch_start_timestamp,
+            )?
+        }
+    };
+    msg!("Tolerance {} slots", tolerance);
+    // Oracles published in the current slot still need a window of one
+    if tolerance == 0 {
+        msg!("Oracle tolerance resolved to 0 slots, no price can be fresh enough");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // An unset oracle list accepts any Pyth products, otherwise the product
+    // accounts (every other account after the clock) must match the table's
+    if honeypot.oracles != [Pubkey::default(); ORACLE_COUNT] {
+        for (i, oracle) in honeypot.oracles.iter().enumerate() {
+            let product_info = oracle_accounts
+                .get(1 + 2 * i)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            assert_keys_equal(*oracle, *product_info.key)?;
+        }
+    }
+    // A price older than the tolerance means the spin came too late to
+    // reveal, which retrying in a later slot won't fix
+    let (random_sample, slot) = R::sample(oracle_accounts, tolerance).map_err(|e| {
+        if e == RandomError::StalePrice.into() {
+            RouletteError::RevealExpired.into()
+        } else {
+            e
+        }
+    })?;
     if !rng.initialized {
         rng.initialized = true;
     }
-    if rng.slot == slot {
-        return Err(RouletteError::InvalidSlot.into());
+    if slot < rng.slot.saturating_add(honeypot.min_reveal_delay) {
+        msg!(
+            "Spin at slot {} is too soon after slot {}, {} slot delay required",
+            slot,
+            rng.slot,
+            honeypot.min_reveal_delay
+        );
+        return Err(RouletteError::RevealTooSoon.into());
     }
     rng.value = random_sample;
     rng.slot = slot;
+    rng.commitment = hashv(&[
+        &random_sample.to_le_bytes(),
+        &slot.to_le_bytes(),
+        gambler_info.key.as_ref(),
+    ])
+    .to_bytes();
     msg!("Sample {}", random_sample);
-    let outcome = rng.value % 38;
+    msg!("Commitment {}", Hash::new_from_array(rng.commitment));
+    let outcome = rng.value % POCKETS;
     msg!("Roulette Outcome {}", outcome);
-    let mut reward: u64 = 0;
-    let mut total_amount: u64 = 0;
+    msg!(
+        "Fairness proof: rng {} gambler {} slot {} sample {} outcome {} = sample % {}",
+        rng_info.key,
+        gambler_info.key,
+        slot,
+        random_sample,
+        outcome,
+        POCKETS
+    );
+    let mut reward = BetUnits::default();
+    let mut total_amount = BetUnits::default();
+    let mut results = Vec::with_capacity(bets.len());
+    let mut straight_amounts = [BetUnits::default(); POCKETS as usize];
     for &bet in bets.iter() {
         msg!("Bet Enum: {}, size: {}", bet.bet as u8, bet.amount);
-        reward = reward
-            .checked_add(bet.get_payout(outcome))
-            .ok_or(RouletteError::NumericalOverflow)?;
-        msg!("Reward {}", reward);
-        total_amount = total_amount
-            .checked_add(bet.amount)
-            .ok_or(RouletteError::NumericalOverflow)?;
+        let payout = bet.get_payout_with_rule(outcome, honeypot.zero_rule)?;
+        results.push(BetResult {
+            bet: bet.bet,
+            amount: bet.amount,
+            payout: payout.get(),
+        });
+        reward = reward.checked_add(payout)?;
+        msg!("Reward {}", reward.get());
+        total_amount = total_amount.checked_add(bet.units())?;
+        if let Some(number) = bet.bet.number() {
+            straight_amounts[number as usize] =
+                straight_amounts[number as usize].checked_add(bet.units())?;
+        }
     }
-    if total_amount
-        .checked_mul(honeypot.tick_size)
-        .ok_or(RouletteError::NumericalOverflow)?
-        > honeypot.max_bet_size
-    {
+    if honeypot.max_straight_bet.get() > 0 {
+        for (number, amount) in straight_amounts.iter().enumerate() {
+            if amount.to_tokens(honeypot.tick_size)? > honeypot.max_straight_bet {
+                msg!("Straight-up bet on {} is too large", number);
+                return Err(RouletteError::AmountTooLarge.into());
+            }
+        }
+    }
+    let total_bet_size = total_amount.to_tokens(honeypot.tick_size)?;
+    if total_bet_size > honeypot.max_bet_size {
         msg!("Bet is too large");
         return Err(RouletteError::AmountTooLarge.into());
     }
-    if vault.amount <= honeypot.minimum_bank_size {
-        msg!("Honeypot funds have been drained. The house needs to reload.");
-        return Err(ProgramError::InsufficientFunds.into());
+    // minimum_bank_size is a reserve the house never pays out of: whatever
+    // the outcome, the vault must keep at least that much once the stake is in
+    let available = vault
+        .amount
+        .checked_add(total_bet_size.get())
+        .ok_or(RouletteError::NumericalOverflow)?
+        .saturating_sub(honeypot.minimum_bank_size);
+    if max_payout.get() > available {
+        msg!(
+            "Bets could win {} tokens but only {} are available above the reserve",
+            max_payout.get(),
+            available
+        );
+        return Err(ProgramError::InsufficientFunds);
+    }
+    msg!("User deposited {} tokens", total_bet_size.get());
+    // Wrapped SOL tables take the stake straight from the gambler's wallet: the
+    // lamports are moved into their (pre-existing) wSOL ATA and synced before the
+    // usual token transfer. Once the spin settles the ATA is closed back into the
+    // wallet, unwrapping any winnings, so clients recreate the ATA before each spin.
+    let is_native = *mint_info.key == spl_token::native_mint::id();
+    if !is_native && gambler_token_account.amount < total_bet_size.get() {
+        msg!(
+            "Gambler holds {} tokens but bet {}",
+            gambler_token_account.amount,
+            total_bet_size.get()
+        );
+        return Err(ProgramError::InsufficientFunds);
+    }
+    if is_native {
+        invoke(
+            &system_instruction::transfer(
+                gambler_info.key,
+                gambler_token_account_info.key,
+                total_bet_size.get(),
+            ),
+            &[
+                gambler_info.clone(),
+                gambler_token_account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+        invoke(
+            &sync_native(token_program_info.key, gambler_token_account_info.key)?,
+            &[
+                gambler_token_account_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
     }
-    let total_bet_size = total_amount
-        .checked_mul(honeypot.tick_size)
-        .ok_or(RouletteError::NumericalOverflow)?;
-    msg!("User deposited {} tokens", total_bet_size);
     invoke(
         &transfer(
             token_program_info.key,
@@ -429,7 +1302,7 @@ fn roulette(
             vault_info.key,
             gambler_info.key,
             &[],
-            total_bet_size,
+            total_bet_size.get(),
         )?,
         &[
             gambler_token_account_info.clone(),
@@ -438,11 +1311,20 @@ fn roulette(
             token_program_info.clone(),
         ],
     )?;
-    let total_reward = reward
-        .checked_mul(honeypot.tick_size)
-        .ok_or(RouletteError::NumericalOverflow)?;
-    if total_reward > 0 {
-        msg!("User won {} tokens", total_reward);
+    let total_reward = reward.to_tokens(honeypot.tick_size)?;
+    if total_reward.get() > 0 {
+        msg!("User won {} tokens", total_reward.get());
+        // A failed transfer would abort with an opaque token program error,
+        // so report a short vault explicitly
+        let vault: Account = assert_initialized(vault_info)?;
+        if vault.amount < total_reward.get() {
+            msg!(
+                "Vault holds {} tokens, cannot pay {}",
+                vault.amount,
+                total_reward.get()
+            );
+            return Err(RouletteError::PayoutFailed.into());
+        }
         invoke_signed(
             &transfer(
                 token_program_info.key,
@@ -450,7 +1332,7 @@ fn roulette(
                 gambler_token_account_info.key,
                 honeypot_info.key,
                 &[],
-                total_reward,
+                total_reward.get(),
             )?,
             &[
                 vault_info.clone(),
@@ -461,6 +1343,447 @@ fn roulette(
             &[honeypot_seeds],
         )?;
     }
+    let vault: Account = assert_initialized(vault_info)?;
+    honeypot.cached_vault_balance = vault.amount;
+    honeypot.serialize(&mut *honeypot_info.data.borrow_mut())?;
+    if vault.amount
+        < honeypot
+            .minimum_bank_size
+            .saturating_add(honeypot.reload_threshold)
+    {
+        msg!("Vault balance {} is running low", vault.amount);
+        let event = LowBankEvent {
+            honeypot: *honeypot_info.key,
+            vault_amount: vault.amount,
+            minimum_bank_size: honeypot.minimum_bank_size,
+            reload_threshold: honeypot.reload_threshold,
+        };
+        sol_log_data(&[b"LowBank", &event.try_to_vec()?]);
+    }
+    if is_native {
+        msg!("Unwrapping SOL");
+        invoke(
+            &close_account(
+                token_program_info.key,
+                gambler_token_account_info.key,
+                gambler_info.key,
+                gambler_info.key,
+                &[],
+            )?,
+            &[
+                gambler_token_account_info.clone(),
+                gambler_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
     rng.serialize(&mut *rng_info.data.borrow_mut())?;
+    let result = RouletteResult {
+        sample: random_sample,
+        slot,
+        outcome,
+        bets: results,
+    };
+    msg!("Net result {}", result.net_result());
+    set_return_data(&result.try_to_vec()?);
+    Ok(())
+}
+
+fn preview_payout(bets: Vec<RouletteBet>, outcome: u8, zero_rule: ZeroRule) -> ProgramResult {
+    if bets.len() > MAX_BETS {
+        msg!("Cannot place more than {} bets in a single spin", MAX_BETS);
+        return Err(RouletteError::TooManyBets.into());
+    }
+    let outcome = outcome as u64;
+    if outcome >= POCKETS {
+        msg!("Outcome {} is not on the wheel", outcome);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut reward = BetUnits::default();
+    for bet in bets.iter() {
+        reward = reward.checked_add(bet.get_payout_with_rule(outcome, zero_rule)?)?;
+    }
+    msg!("Outcome {} pays {}", outcome, reward.get());
+    set_return_data(&reward.get().to_le_bytes());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{PreviewPayoutArgs, RouletteArgs, SampleArgs, SetSignersArgs};
+    use crate::random_source::FixedRandomSource;
+    use crate::state::Bet;
+    use crate::test_utils::{install_stubs, process, take_return_data, Table, TestAccount};
+
+    #[test]
+    fn legacy_rng_reads_with_zeroed_commitment() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![1u8];
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&9u64.to_le_bytes());
+        assert_eq!(data.len(), RNG::LEGACY_LEN);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(RNG::from_account_info(&info).is_err());
+        let rng = RNG::from_legacy_account_info(&info).unwrap();
+        assert!(rng.initialized);
+        assert_eq!((rng.value, rng.slot, rng.commitment), (7, 9, [0; 32]));
+    }
+
+    // try_from_slice rejects trailing bytes, so a zeroed LEN buffer only
+    // decodes if LEN is exactly the serialized size
+    #[test]
+    fn rng_len_matches_layout() {
+        let rng = RNG::try_from_slice(&[0u8; RNG::LEN]).unwrap();
+        assert_eq!(rng.try_to_vec().unwrap().len(), RNG::LEN);
+    }
+
+    #[test]
+    fn honeypot_len_matches_layout() {
+        let honeypot = Honeypot::try_from_slice(&[0u8; Honeypot::LEN]).unwrap();
+        assert_eq!(honeypot.try_to_vec().unwrap().len(), Honeypot::LEN);
+        assert!(Honeypot::try_from_slice(&[0u8; Honeypot::LEN + 1]).is_err());
+    }
+
+    fn red_bet() -> Vec<RouletteBet> {
+        vec![RouletteBet::new(Bet::Red, 1).unwrap()]
+    }
+
+    #[test]
+    fn zero_tolerance_uses_the_table_default() {
+        let mut table = Table::new();
+        table.spin(0, 100, 0, red_bet()).unwrap();
+        assert_eq!(FixedRandomSource::last_tolerance(), 10);
+    }
+
+    #[test]
+    fn tolerance_below_the_default_is_kept() {
+        let mut table = Table::new();
+        table.spin(0, 100, 4, red_bet()).unwrap();
+        assert_eq!(FixedRandomSource::last_tolerance(), 4);
+    }
+
+    #[test]
+    fn tolerance_above_the_default_is_clamped() {
+        let mut table = Table::new();
+        table.spin(0, 100, 1_000, red_bet()).unwrap();
+        assert_eq!(FixedRandomSource::last_tolerance(), 10);
+    }
+
+    #[test]
+    fn spin_respects_min_reveal_delay() {
+        for (spin_slot, allowed) in [(104, false), (105, true), (106, true)] {
+            let mut table = Table::new();
+            table.update_honeypot(|honeypot| honeypot.min_reveal_delay = 5);
+            table.sample(0, 100).unwrap();
+            let result = table.spin(0, spin_slot, 0, red_bet());
+            if allowed {
+                assert!(result.is_ok(), "slot {}", spin_slot);
+            } else {
+                assert_eq!(
+                    result.unwrap_err(),
+                    RouletteError::RevealTooSoon.into(),
+                    "slot {}",
+                    spin_slot
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_rng_owner_can_sample() {
+        let mut table = Table::new();
+        table.sample(0, 100).unwrap();
+        FixedRandomSource::set(0, 200);
+        let mut stranger =
+            TestAccount::new(Pubkey::new_unique(), system_program::id(), vec![]).signer();
+        let accounts = [table.rng.info(), stranger.info()];
+        assert_eq!(
+            process(
+                &table.program_id,
+                &accounts,
+                RandomInstruction::Sample(SampleArgs { tolerance: 10 })
+            ),
+            Err(UtilError::PublicKeyMismatch.into())
+        );
+        table.gambler.is_signer = false;
+        let accounts = [table.rng.info(), table.gambler.info()];
+        assert_eq!(
+            process(
+                &table.program_id,
+                &accounts,
+                RandomInstruction::Sample(SampleArgs { tolerance: 10 })
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(table.rng().slot, 100);
+    }
+
+    #[test]
+    fn spin_commitment_survives_sample() {
+        let mut table = Table::new();
+        table.spin(12_345, 100, 0, red_bet()).unwrap();
+        let mut preimage = 12_345u64.to_le_bytes().to_vec();
+        preimage.extend_from_slice(&100u64.to_le_bytes());
+        preimage.extend_from_slice(table.gambler.key.as_ref());
+        let expected = solana_program::hash::hash(&preimage).to_bytes();
+        assert_eq!(table.rng().commitment, expected);
+        table.sample(6_789, 200).unwrap();
+        assert_eq!(table.rng().commitment, expected);
+    }
+
+    #[test]
+    fn no_spin_pays_more_than_the_max_potential_payout() {
+        let bets = vec![
+            RouletteBet::new(Bet::Red, 10).unwrap(),
+            RouletteBet::new(Bet::Zero, 1).unwrap(),
+            RouletteBet::new(Bet::Dozen2, 4).unwrap(),
+            RouletteBet::new(Bet::DoubleZero, 2).unwrap(),
+        ];
+        let stake = 17 * 3;
+        let mut seed: u64 = 0x853c_49e6_748f_ea9b;
+        for zero_rule in [ZeroRule::None, ZeroRule::LaPartage] {
+            let mut table = Table::with_params(3, 0);
+            table.update_honeypot(|honeypot| honeypot.zero_rule = zero_rule);
+            let max_payout = max_potential_payout(&bets, zero_rule)
+                .unwrap()
+                .to_tokens(3)
+                .unwrap();
+            let mut best = 0;
+            for slot in 1..2_000 {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                let before = table.gambler_token_account.token_amount();
+                table.spin(seed, slot, 0, bets.clone()).unwrap();
+                let paid = table.gambler_token_account.token_amount() + stake - before;
+                assert!(paid <= max_payout.get(), "sample {} paid {}", seed, paid);
+                best = best.max(paid);
+            }
+            // Some sample hit the best pocket, so the bound is also tight
+            assert_eq!(best, max_payout.get());
+        }
+    }
+
+    #[test]
+    fn spin_rejects_an_unmigrated_honeypot() {
+        let mut table = Table::new();
+        table.update_honeypot(|honeypot| honeypot.version = 0);
+        assert_eq!(
+            table.spin(0, 100, 0, red_bet()),
+            Err(UtilError::VersionMismatch.into())
+        );
+    }
+
+    fn signer_account(key: Pubkey) -> TestAccount {
+        TestAccount::new(key, system_program::id(), vec![]).signer()
+    }
+
+    fn multisig_honeypot() -> (Honeypot, [Pubkey; MAX_SIGNERS]) {
+        let mut honeypot = Honeypot::try_from_slice(&[0; Honeypot::LEN]).unwrap();
+        let signers = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        honeypot.owner = Pubkey::new_unique();
+        honeypot.signers = signers;
+        honeypot.threshold = 2;
+        (honeypot, signers)
+    }
+
+    #[test]
+    fn two_of_three_signers_have_authority() {
+        let (honeypot, signers) = multisig_honeypot();
+        let mut owner = signer_account(honeypot.owner);
+        let mut first = signer_account(signers[0]);
+        let mut third = signer_account(signers[2]);
+        let cosigners = [first.info(), third.info()];
+        assert_eq!(honeypot.assert_authority(&owner.info(), &cosigners), Ok(()));
+    }
+
+    #[test]
+    fn one_of_three_signers_lacks_authority() {
+        let (honeypot, signers) = multisig_honeypot();
+        let mut owner = signer_account(honeypot.owner);
+        let mut first = signer_account(signers[0]);
+        let cosigners = [first.info()];
+        assert_eq!(
+            honeypot.assert_authority(&owner.info(), &cosigners),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut repeated = signer_account(signers[0]);
+        let cosigners = [first.info(), repeated.info()];
+        assert_eq!(
+            honeypot.assert_authority(&owner.info(), &cosigners),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn set_signers_rejects_duplicates() {
+        let mut table = Table::new();
+        let mut owner = signer_account(table.honeypot().owner);
+        let duplicate = Pubkey::new_unique();
+        let accounts = [table.honeypot.info(), owner.info()];
+        assert_eq!(
+            process(
+                &table.program_id,
+                &accounts,
+                RandomInstruction::SetSigners(SetSignersArgs {
+                    signers: [duplicate, Pubkey::new_unique(), duplicate],
+                    threshold: 2,
+                })
+            ),
+            Err(UtilError::PublicKeysShouldBeUnique.into())
+        );
+        assert_eq!(table.honeypot().signers, [Pubkey::default(); MAX_SIGNERS]);
+    }
+
+    fn preview(outcome: u8, zero_rule: ZeroRule) -> u64 {
+        install_stubs();
+        let bets = vec![
+            RouletteBet::new(Bet::Red, 4).unwrap(),
+            RouletteBet::new(Bet::Zero, 1).unwrap(),
+        ];
+        let instruction = RandomInstruction::PreviewPayout(PreviewPayoutArgs {
+            bets,
+            outcome,
+            zero_rule,
+        });
+        process(&Pubkey::new_unique(), &[], instruction).unwrap();
+        u64::try_from_slice(&take_return_data().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn preview_payout_applies_the_zero_rule() {
+        assert_eq!(preview(0, ZeroRule::None), 36);
+        assert_eq!(preview(0, ZeroRule::LaPartage), 36 + 2);
+        assert_eq!(preview(37, ZeroRule::LaPartage), 2);
+        assert_eq!(preview(1, ZeroRule::LaPartage), 8);
+    }
+
+    fn timelocked_table() -> Table {
+        let mut table = Table::new();
+        table.update_honeypot(|honeypot| honeypot.withdraw_timelock_slots = 100);
+        table
+    }
+
+    #[test]
+    fn withdraw_before_the_timelock_is_locked() {
+        let mut table = timelocked_table();
+        table.request_withdraw(500, 1_000).unwrap();
+        assert_eq!(
+            table.withdraw(500, 1_099),
+            Err(RouletteError::WithdrawLocked.into())
+        );
+        assert_eq!(table.owner_token_account.token_amount(), 0);
+    }
+
+    #[test]
+    fn withdraw_after_the_timelock_pays_out() {
+        let mut table = timelocked_table();
+        table.request_withdraw(500, 1_000).unwrap();
+        table.withdraw(500, 1_100).unwrap();
+        assert_eq!(table.owner_token_account.token_amount(), 500);
+        let honeypot = table.honeypot();
+        assert_eq!(honeypot.pending_withdrawal, 0);
+        assert_eq!(honeypot.cached_vault_balance, Table::VAULT_BALANCE - 500);
+        // The request is spent
+        assert_eq!(
+            table.withdraw(500, 1_100),
+            Err(RouletteError::WithdrawLocked.into())
+        );
+    }
+
+    #[test]
+    fn withdraw_request_lapses_after_the_window() {
+        let mut table = timelocked_table();
+        table.request_withdraw(500, 1_000).unwrap();
+        assert_eq!(
+            table.withdraw(500, 1_100 + WITHDRAW_WINDOW_SLOTS + 1),
+            Err(RouletteError::WithdrawLocked.into())
+        );
+        table.withdraw(500, 1_100 + WITHDRAW_WINDOW_SLOTS).unwrap();
+        assert_eq!(table.owner_token_account.token_amount(), 500);
+    }
+
+    #[test]
+    fn withdraw_must_match_the_requested_amount() {
+        let mut table = timelocked_table();
+        table.request_withdraw(500, 1_000).unwrap();
+        for amount in [499, 501, WITHDRAW_ALL] {
+            assert_eq!(
+                table.withdraw(amount, 1_100),
+                Err(RouletteError::WithdrawLocked.into())
+            );
+        }
+        assert_eq!(table.owner_token_account.token_amount(), 0);
+    }
+
+    #[test]
+    fn withdraw_all_can_be_requested() {
+        let mut table = Table::with_params(1, 1_000);
+        table.update_honeypot(|honeypot| honeypot.withdraw_timelock_slots = 100);
+        table.request_withdraw(WITHDRAW_ALL, 1_000).unwrap();
+        assert_eq!(
+            table.withdraw(Table::VAULT_BALANCE - 1_000, 1_100),
+            Err(RouletteError::WithdrawLocked.into())
+        );
+        table.withdraw(WITHDRAW_ALL, 1_100).unwrap();
+        assert_eq!(
+            table.owner_token_account.token_amount(),
+            Table::VAULT_BALANCE - 1_000
+        );
+        assert_eq!(table.vault.token_amount(), 1_000);
+    }
+
+    #[test]
+    fn stale_oracle_price_expires_the_reveal() {
+        let mut table = Table::new();
+        FixedRandomSource::set(0, 100);
+        FixedRandomSource::fail(RandomError::StalePrice.into());
+        let program_id = table.program_id;
+        let accounts = table.roulette_accounts();
+        assert_eq!(
+            process(
+                &program_id,
+                &accounts,
+                RandomInstruction::Roulette(RouletteArgs {
+                    tolerance: 0,
+                    bets: red_bet(),
+                })
+            ),
+            Err(RouletteError::RevealExpired.into())
+        );
+    }
+
+    #[test]
+    fn other_sampling_errors_pass_through() {
+        let mut table = Table::new();
+        FixedRandomSource::set(0, 100);
+        FixedRandomSource::fail(ProgramError::InvalidArgument);
+        let program_id = table.program_id;
+        let accounts = table.roulette_accounts();
+        assert_eq!(
+            process(
+                &program_id,
+                &accounts,
+                RandomInstruction::Roulette(RouletteArgs {
+                    tolerance: 0,
+                    bets: red_bet(),
+                })
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use solana_program::{account_info::AccountInfo, program_error::ProgramError};
+
+pub trait RandomSource {
+    // Returns the random sample and the slot it was taken in
+    fn sample(accounts: &[AccountInfo], tolerance: u64) -> Result<(u64, u64), ProgramError>;
+}
+
+pub struct PythRandomSource;
+
+impl RandomSource for PythRandomSource {
+    fn sample(accounts: &[AccountInfo], tolerance: u64) -> Result<(u64, u64), ProgramError> {
+        random::random::sample(accounts, tolerance)
+    }
+}
+
+// Hands back whatever sample the test last set, so payouts can be checked
+// without Pyth accounts, and remembers the tolerance it was asked for
+#[cfg(test)]
+pub struct FixedRandomSource;
+
+#[cfg(test)]
+thread_local! {
+    static FIXED_SAMPLE: std::cell::Cell<(u64, u64)> = const { std::cell::Cell::new((0, 0)) };
+    static LAST_TOLERANCE: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    static FIXED_ERROR: std::cell::RefCell<Option<ProgramError>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(test)]
+impl FixedRandomSource {
+    pub fn set(sample: u64, slot: u64) {
+        FIXED_SAMPLE.with(|fixed| fixed.set((sample, slot)));
+        FIXED_ERROR.with(|error| *error.borrow_mut() = None);
+    }
+
+    // Fails every sample with `error` until the next set
+    pub fn fail(error: ProgramError) {
+        FIXED_ERROR.with(|fixed| *fixed.borrow_mut() = Some(error));
+    }
+
+    pub fn last_tolerance() -> u64 {
+        LAST_TOLERANCE.with(|last| last.get())
+    }
+}
+
+#[cfg(test)]
+impl RandomSource for FixedRandomSource {
+    fn sample(_accounts: &[AccountInfo], tolerance: u64) -> Result<(u64, u64), ProgramError> {
+        LAST_TOLERANCE.with(|last| last.set(tolerance));
+        if let Some(error) = FIXED_ERROR.with(|fixed| fixed.borrow().clone()) {
+            return Err(error);
+        }
+        Ok(FIXED_SAMPLE.with(|fixed| fixed.get()))
+    }
+}
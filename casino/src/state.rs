@@ -1,4 +1,22 @@
+use crate::error::RouletteError;
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{clock::DEFAULT_MS_PER_SLOT, program_error::ProgramError, pubkey::Pubkey};
+use std::convert::TryFrom;
+
+// American wheel: 0, 00 (outcome 37) and 1 through 36
+pub const POCKETS: u64 = 38;
+
+pub const MAX_SIGNERS: usize = 3;
+
+pub const ORACLE_COUNT: usize = 3;
+
+pub const MAX_DENOMINATIONS: usize = 4;
+
+// Straight-up bets pay 35 to 1, the highest multiplier on the table
+pub const STRAIGHT_MULTIPLIER: u64 = 36;
+
+// 38 straight-up bets and 12 outside bets
+pub const BET_TYPES: usize = 50;
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Copy)]
@@ -55,6 +73,129 @@ pub enum Bet {
     High,
 }
 
+impl Bet {
+    // This bet's bit in a honeypot's disabled_bets mask
+    pub fn mask(&self) -> u64 {
+        1 << (*self as u64)
+    }
+
+    pub fn from_index(idx: u8) -> Option<Bet> {
+        Bet::try_from_slice(&[idx]).ok()
+    }
+
+    // Per unit staked, including the stake itself; 0 when the bet loses
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn multiplier(&self, outcome: u64) -> u64 {
+        let (wins, multiplier) = match *self {
+            Bet::Red => (outcome != 0 && outcome != 37 && is_red(outcome), 2),
+            Bet::Black => (outcome != 0 && outcome != 37 && !is_red(outcome), 2),
+            Bet::Even => (outcome != 0 && outcome % 2 == 0, 2),
+            Bet::Odd => (outcome != 37 && outcome % 2 == 1, 2),
+            Bet::Col1 => (outcome != 37 && outcome % 3 == 1, 3),
+            Bet::Col2 => (outcome % 3 == 2, 3),
+            Bet::Col3 => (outcome != 0 && outcome % 3 == 0, 3),
+            Bet::Dozen1 => (outcome > 0 && outcome <= 12, 3),
+            Bet::Dozen2 => (outcome > 12 && outcome <= 24, 3),
+            Bet::Dozen3 => (outcome > 24 && outcome < 37, 3),
+            Bet::Low => (outcome > 0 && outcome <= 18, 2),
+            Bet::High => (outcome > 18 && outcome < 37, 2),
+            _ => (self.number() == Some(outcome), STRAIGHT_MULTIPLIER),
+        };
+        if wins {
+            multiplier
+        } else {
+            0
+        }
+    }
+
+    pub fn is_even_money(&self) -> bool {
+        matches!(
+            self,
+            Bet::Red | Bet::Black | Bet::Even | Bet::Odd | Bet::Low | Bet::High
+        )
+    }
+
+    // Straight-up bets map to their pocket, 00 being outcome 37
+    pub fn number(&self) -> Option<u64> {
+        match *self {
+            Bet::Zero => Some(0),
+            Bet::DoubleZero => Some(37),
+            bet if bet as u64 <= Bet::R36 as u64 => Some(bet as u64 - 1),
+            _ => None,
+        }
+    }
+
+    // The outcomes this bet wins on, 37 standing for 00. Only wins count:
+    // the half stake La Partage hands back on 0 and 00 is not a win
+    pub fn covered_numbers(&self) -> Vec<u64> {
+        let unit = RouletteBet {
+            bet: *self,
+            amount: 1,
+        };
+        (0..POCKETS)
+            .filter(|&outcome| matches!(unit.get_payout(outcome), Ok(payout) if payout.0 > 0))
+            .collect()
+    }
+
+    // Staking 2 units keeps the half stake returned under La Partage exact
+    pub fn house_edge_bps(&self, zero_rule: ZeroRule) -> u32 {
+        let unit = RouletteBet {
+            bet: *self,
+            amount: 2,
+        };
+        let staked = unit.amount * POCKETS;
+        let returned: u64 = (0..POCKETS)
+            .filter_map(|outcome| unit.get_payout_with_rule(outcome, zero_rule).ok())
+            .map(|payout| payout.0)
+            .sum();
+        (staked.saturating_sub(returned) * 10_000 / staked) as u32
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Copy)]
+pub enum ZeroRule {
+    None,
+    // Even-money bets get half their stake back when the ball lands on 0 or 00
+    LaPartage,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Copy)]
+pub enum ToleranceUnit {
+    Slots,
+    Seconds,
+}
+
+impl ToleranceUnit {
+    // Seconds are converted at the average slot time observed so far this
+    // epoch, rounding up so the window never comes out shorter than asked
+    pub fn to_slots(
+        self,
+        tolerance: u64,
+        slot: u64,
+        unix_timestamp: i64,
+        epoch_start_slot: u64,
+        epoch_start_timestamp: i64,
+    ) -> Result<u64, ProgramError> {
+        if self == ToleranceUnit::Slots {
+            return Ok(tolerance);
+        }
+        let elapsed_slots = slot.saturating_sub(epoch_start_slot) as u128;
+        let elapsed_ms = unix_timestamp.saturating_sub(epoch_start_timestamp).max(0) as u128 * 1000;
+        let (slots, ms) = if elapsed_slots == 0 || elapsed_ms == 0 {
+            (1, DEFAULT_MS_PER_SLOT as u128)
+        } else {
+            (elapsed_slots, elapsed_ms)
+        };
+        let window = (tolerance as u128)
+            .checked_mul(1000)
+            .and_then(|t| t.checked_mul(slots))
+            .ok_or(RouletteError::NumericalOverflow)?;
+        u64::try_from(window.div_ceil(ms)).map_err(|_| RouletteError::NumericalOverflow.into())
+    }
+}
+
 pub fn is_red(number: u64) -> bool {
     let red_numbers: Vec<u64> = vec![
         1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
@@ -62,6 +203,52 @@ pub fn is_red(number: u64) -> bool {
     red_numbers.contains(&number)
 }
 
+// Stakes and payouts are counted in bet units, each worth tick_size tokens
+#[derive(
+    BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct BetUnits(u64);
+
+// Amounts of the table's mint, in its smallest denomination
+#[derive(
+    BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct Tokens(u64);
+
+impl BetUnits {
+    pub const fn new(units: u64) -> BetUnits {
+        BetUnits(units)
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: BetUnits) -> Result<BetUnits, ProgramError> {
+        self.0
+            .checked_add(other.0)
+            .map(BetUnits)
+            .ok_or_else(|| RouletteError::NumericalOverflow.into())
+    }
+
+    pub fn to_tokens(self, tick_size: u64) -> Result<Tokens, ProgramError> {
+        self.0
+            .checked_mul(tick_size)
+            .map(Tokens)
+            .ok_or_else(|| RouletteError::NumericalOverflow.into())
+    }
+}
+
+impl Tokens {
+    pub const fn new(amount: u64) -> Tokens {
+        Tokens(amount)
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Copy)]
 pub struct RouletteBet {
@@ -69,359 +256,354 @@ pub struct RouletteBet {
     pub amount: u64,
 }
 
+// Spins the wheel n times, landing on each pocket in turn, and returns the
+// total wagered and total paid out in bet units
+#[cfg(any(test, feature = "sim"))]
+pub fn simulate_outcomes(bets: &[RouletteBet], n: u64) -> (u128, u128) {
+    let mut total_wagered: u128 = 0;
+    let mut total_paid: u128 = 0;
+    for spin in 0..n {
+        let outcome = spin % POCKETS;
+        for bet in bets.iter() {
+            total_wagered += bet.amount as u128;
+            total_paid += bet
+                .get_payout(outcome)
+                .map(|payout| payout.0 as u128)
+                .unwrap_or(0);
+        }
+    }
+    (total_wagered, total_paid)
+}
+
+// The most a set of bets can win across every pocket on the wheel
+pub fn max_potential_payout(
+    bets: &[RouletteBet],
+    zero_rule: ZeroRule,
+) -> Result<BetUnits, ProgramError> {
+    let mut max_payout = BetUnits::default();
+    for outcome in 0..POCKETS {
+        let mut payout = BetUnits::default();
+        for bet in bets.iter() {
+            payout = payout.checked_add(bet.get_payout_with_rule(outcome, zero_rule)?)?;
+        }
+        max_payout = max_payout.max(payout);
+    }
+    Ok(max_payout)
+}
+
+// Keeps the serialized RouletteResult within the runtime's return data limit
+pub const MAX_BETS: usize = 50;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Copy)]
+pub struct BetResult {
+    pub bet: Bet,
+    pub amount: u64,
+    pub payout: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RouletteResult {
+    pub sample: u64,
+    pub slot: u64,
+    pub outcome: u64,
+    pub bets: Vec<BetResult>,
+}
+
+impl BetResult {
+    // Payout minus stake, in bet units
+    pub fn net_result(&self) -> i128 {
+        self.payout as i128 - self.amount as i128
+    }
+}
+
+impl RouletteResult {
+    pub fn net_result(&self) -> i128 {
+        self.bets.iter().map(BetResult::net_result).sum()
+    }
+}
+
 impl RouletteBet {
-    pub fn get_payout(&self, outcome: u64) -> u64 {
-        match self.bet {
-            Bet::Zero => {
-                if outcome == 0 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::DoubleZero => {
-                if outcome == 37 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R1 => {
-                if outcome == 1 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B2 => {
-                if outcome == 2 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R3 => {
-                if outcome == 3 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B4 => {
-                if outcome == 4 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R5 => {
-                if outcome == 5 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B6 => {
-                if outcome == 6 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R7 => {
-                if outcome == 7 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B8 => {
-                if outcome == 8 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R9 => {
-                if outcome == 9 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B10 => {
-                if outcome == 10 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B11 => {
-                if outcome == 11 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R12 => {
-                if outcome == 12 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B13 => {
-                if outcome == 13 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R14 => {
-                if outcome == 14 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B15 => {
-                if outcome == 15 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R16 => {
-                if outcome == 16 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B17 => {
-                if outcome == 17 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R18 => {
-                if outcome == 18 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R19 => {
-                if outcome == 19 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B20 => {
-                if outcome == 20 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R21 => {
-                if outcome == 21 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B22 => {
-                if outcome == 22 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R23 => {
-                if outcome == 23 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B24 => {
-                if outcome == 24 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R25 => {
-                if outcome == 25 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B26 => {
-                if outcome == 26 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R27 => {
-                if outcome == 27 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B28 => {
-                if outcome == 28 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B29 => {
-                if outcome == 29 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R30 => {
-                if outcome == 30 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B31 => {
-                if outcome == 31 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R32 => {
-                if outcome == 32 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B33 => {
-                if outcome == 33 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R34 => {
-                if outcome == 34 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::B35 => {
-                if outcome == 35 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::R36 => {
-                if outcome == 36 {
-                    self.amount * 36
-                } else {
-                    0
-                }
-            }
-            Bet::Red => {
-                if outcome != 0 && outcome != 37 && is_red(outcome) {
-                    self.amount * 2
-                } else {
-                    0
-                }
-            }
-            Bet::Black => {
-                if outcome != 0 && outcome != 37 && !is_red(outcome) {
-                    self.amount * 2
-                } else {
-                    0
-                }
-            }
-            Bet::Even => {
-                if outcome != 0 && outcome % 2 == 0 {
-                    self.amount * 2
-                } else {
-                    0
-                }
-            }
-            Bet::Odd => {
-                if outcome != 37 && outcome % 2 == 1 {
-                    self.amount * 2
-                } else {
-                    0
-                }
-            }
-            Bet::Col1 => {
-                if outcome != 37 && outcome % 3 == 1 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Col2 => {
-                if outcome % 3 == 2 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Col3 => {
-                if outcome != 0 && outcome % 3 == 0 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Dozen1 => {
-                if outcome > 0 && outcome <= 12 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Dozen2 => {
-                if outcome > 12 && outcome <= 24 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Dozen3 => {
-                if outcome > 24 && outcome < 37 {
-                    self.amount * 3
-                } else {
-                    0
-                }
-            }
-            Bet::Low => {
-                if outcome > 0 && outcome <= 18 {
-                    self.amount * 2
-                } else {
-                    0
-                }
+    pub fn new(bet: Bet, amount: u64) -> Result<Self, RouletteError> {
+        let roulette_bet = RouletteBet { bet, amount };
+        roulette_bet.validate()?;
+        Ok(roulette_bet)
+    }
+
+    pub fn validate(&self) -> Result<(), RouletteError> {
+        if self.amount == 0 {
+            return Err(RouletteError::ZeroBet);
+        }
+        Ok(())
+    }
+
+    pub fn units(&self) -> BetUnits {
+        BetUnits(self.amount)
+    }
+
+    pub fn get_payout_with_rule(
+        &self,
+        outcome: u64,
+        zero_rule: ZeroRule,
+    ) -> Result<BetUnits, ProgramError> {
+        if zero_rule == ZeroRule::LaPartage
+            && (outcome == 0 || outcome == 37)
+            && self.bet.is_even_money()
+        {
+            return Ok(BetUnits(self.amount / 2));
+        }
+        self.amount
+            .checked_mul(self.bet.multiplier(outcome))
+            .map(BetUnits)
+            .ok_or_else(|| RouletteError::NumericalOverflow.into())
+    }
+
+    pub fn get_payout(&self, outcome: u64) -> Result<BetUnits, ProgramError> {
+        self.get_payout_with_rule(outcome, ZeroRule::None)
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct LowBankEvent {
+    pub honeypot: Pubkey,
+    pub vault_amount: u64,
+    pub minimum_bank_size: u64,
+    pub reload_threshold: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bet_units_checked_add() {
+        let sum = BetUnits::new(2).checked_add(BetUnits::new(3)).unwrap();
+        assert_eq!(sum, BetUnits::new(5));
+        assert!(BetUnits::new(u64::MAX)
+            .checked_add(BetUnits::new(1))
+            .is_err());
+    }
+
+    #[test]
+    fn bet_units_to_tokens_scales_by_tick_size() {
+        assert_eq!(
+            BetUnits::new(7).to_tokens(1_000).unwrap(),
+            Tokens::new(7_000)
+        );
+        assert!(BetUnits::new(u64::MAX).to_tokens(2).is_err());
+    }
+
+    #[test]
+    fn newtypes_serialize_as_u64() {
+        assert_eq!(
+            BetUnits::new(42).try_to_vec().unwrap(),
+            42u64.try_to_vec().unwrap()
+        );
+        assert_eq!(
+            Tokens::new(42).try_to_vec().unwrap(),
+            42u64.try_to_vec().unwrap()
+        );
+        assert_eq!(
+            Tokens::try_from_slice(&9u64.to_le_bytes()).unwrap(),
+            Tokens::new(9)
+        );
+    }
+
+    #[test]
+    fn simulated_edge_matches_american_wheel() {
+        // Every bet on a double-zero wheel gives up 2 pockets in 38, 526 bps
+        for bet in [Bet::Red, Bet::Col2, Bet::Dozen3, Bet::B17, Bet::DoubleZero] {
+            let bets = [RouletteBet { bet, amount: 5 }];
+            let (wagered, paid) = simulate_outcomes(&bets, POCKETS * 1_000);
+            assert_eq!((wagered - paid) * 10_000 / wagered, 526, "{:?}", bet);
+        }
+    }
+
+    #[test]
+    fn house_edge_follows_zero_rule() {
+        assert_eq!(Bet::Red.house_edge_bps(ZeroRule::None), 526);
+        assert_eq!(Bet::Red.house_edge_bps(ZeroRule::LaPartage), 263);
+        // La Partage only refunds even-money bets
+        assert_eq!(Bet::B17.house_edge_bps(ZeroRule::LaPartage), 526);
+        assert_eq!(Bet::Dozen1.house_edge_bps(ZeroRule::LaPartage), 526);
+    }
+
+    #[test]
+    fn covered_numbers_ignore_la_partage_refunds() {
+        let red = Bet::Red.covered_numbers();
+        assert_eq!(red.len(), 18);
+        assert!(red.iter().all(|&n| is_red(n)));
+        assert!(!red.contains(&0) && !red.contains(&37));
+        assert_eq!(Bet::DoubleZero.covered_numbers(), vec![37]);
+    }
+
+    // Pockets each outside bet wins on, written out by hand from the table
+    // layout rather than derived from the code under test
+    const RED: [u64; 18] = [
+        1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+    ];
+
+    // Straight bets are named after their pocket, R17 or B2, with 00 as
+    // DoubleZero. The colour prefix is checked against RED on the way
+    fn straight_pocket(bet: Bet) -> u64 {
+        let name = format!("{:?}", bet);
+        match name.as_str() {
+            "Zero" => 0,
+            "DoubleZero" => 37,
+            _ => {
+                let number: u64 = name[1..].parse().unwrap();
+                assert_eq!(name.starts_with('R'), RED.contains(&number), "{}", name);
+                number
             }
-            Bet::High => {
-                if outcome > 18 && outcome < 37 {
-                    self.amount * 2
-                } else {
-                    0
-                }
+        }
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    fn baseline_multiplier(bet: Bet, outcome: u64) -> u64 {
+        let number = outcome;
+        let on_table = (1..=36).contains(&number);
+        let wins = match bet {
+            Bet::Red => RED.contains(&number),
+            Bet::Black => on_table && !RED.contains(&number),
+            Bet::Even => on_table && number % 2 == 0,
+            Bet::Odd => on_table && number % 2 == 1,
+            Bet::Col1 => on_table && number % 3 == 1,
+            Bet::Col2 => on_table && number % 3 == 2,
+            Bet::Col3 => on_table && number % 3 == 0,
+            Bet::Dozen1 => (1..=12).contains(&number),
+            Bet::Dozen2 => (13..=24).contains(&number),
+            Bet::Dozen3 => (25..=36).contains(&number),
+            Bet::Low => (1..=18).contains(&number),
+            Bet::High => (19..=36).contains(&number),
+            straight => straight_pocket(straight) == outcome,
+        };
+        let multiplier = match bet {
+            Bet::Red | Bet::Black | Bet::Even | Bet::Odd | Bet::Low | Bet::High => 2,
+            Bet::Col1 | Bet::Col2 | Bet::Col3 | Bet::Dozen1 | Bet::Dozen2 | Bet::Dozen3 => 3,
+            _ => STRAIGHT_MULTIPLIER,
+        };
+        if wins {
+            multiplier
+        } else {
+            0
+        }
+    }
+
+    #[test]
+    fn payouts_match_baseline_table() {
+        for idx in 0..BET_TYPES as u8 {
+            let bet = Bet::from_index(idx).unwrap();
+            for outcome in 0..POCKETS {
+                let expected = baseline_multiplier(bet, outcome) * 5;
+                let payout = RouletteBet { bet, amount: 5 }.get_payout(outcome).unwrap();
+                assert_eq!(payout.get(), expected, "{:?} on {}", bet, outcome);
             }
         }
+        assert!(Bet::from_index(BET_TYPES as u8).is_none());
+    }
+
+    #[test]
+    fn seconds_convert_at_the_observed_slot_time() {
+        // 1000 slots over 500 seconds, 500ms a slot
+        let slots = ToleranceUnit::Seconds.to_slots(10, 3_000, 1_500, 2_000, 1_000);
+        assert_eq!(slots, Ok(20));
+        assert_eq!(
+            ToleranceUnit::Slots.to_slots(10, 3_000, 1_500, 2_000, 1_000),
+            Ok(10)
+        );
+    }
+
+    #[test]
+    fn seconds_fall_back_to_the_default_slot_time() {
+        // No slots or no time elapsed this epoch, so 400ms a slot
+        assert_eq!(
+            ToleranceUnit::Seconds.to_slots(10, 2_000, 1_500, 2_000, 1_000),
+            Ok(25)
+        );
+        assert_eq!(
+            ToleranceUnit::Seconds.to_slots(10, 3_000, 1_000, 2_000, 1_000),
+            Ok(25)
+        );
+        assert_eq!(
+            ToleranceUnit::Seconds.to_slots(10, 3_000, 900, 2_000, 1_000),
+            Ok(25)
+        );
+    }
+
+    #[test]
+    fn seconds_round_up_to_whole_slots() {
+        // 1000 slots over 300 seconds: one second is 3.33 slots
+        assert_eq!(ToleranceUnit::Seconds.to_slots(1, 1_000, 300, 0, 0), Ok(4));
+        assert_eq!(ToleranceUnit::Seconds.to_slots(3, 1_000, 300, 0, 0), Ok(10));
+    }
+
+    #[test]
+    fn seconds_conversion_overflow_is_an_error() {
+        let overflow = Err(RouletteError::NumericalOverflow.into());
+        // The window itself overflows u128
+        assert_eq!(
+            ToleranceUnit::Seconds.to_slots(u64::MAX, u64::MAX, 1, 0, 0),
+            overflow
+        );
+        // The window fits u128 but not the u64 slot count
+        assert_eq!(
+            ToleranceUnit::Seconds.to_slots(u64::MAX, 1_000, 1, 0, 0),
+            overflow
+        );
+    }
+
+    fn bet_result(amount: u64, payout: u64) -> BetResult {
+        BetResult {
+            bet: Bet::Red,
+            amount,
+            payout,
+        }
+    }
+
+    fn roulette_result(bets: Vec<BetResult>) -> RouletteResult {
+        RouletteResult {
+            sample: 0,
+            slot: 0,
+            outcome: 0,
+            bets,
+        }
+    }
+
+    #[test]
+    fn bet_net_result_is_payout_minus_stake() {
+        assert_eq!(bet_result(5, 10).net_result(), 5);
+        assert_eq!(bet_result(5, 0).net_result(), -5);
+        assert_eq!(bet_result(5, 5).net_result(), 0);
+        assert_eq!(bet_result(u64::MAX, 0).net_result(), -(u64::MAX as i128));
+    }
+
+    #[test]
+    fn roulette_net_result_sums_every_bet() {
+        let win = roulette_result(vec![bet_result(1, 36), bet_result(10, 0)]);
+        assert_eq!(win.net_result(), 25);
+        let loss = roulette_result(vec![bet_result(4, 0), bet_result(2, 0)]);
+        assert_eq!(loss.net_result(), -6);
+        let even = roulette_result(vec![bet_result(3, 6), bet_result(3, 0)]);
+        assert_eq!(even.net_result(), 0);
+        assert_eq!(roulette_result(vec![]).net_result(), 0);
+    }
+
+    #[test]
+    fn roulette_bet_new_rejects_zero_amount() {
+        assert!(matches!(
+            RouletteBet::new(Bet::Red, 0),
+            Err(RouletteError::ZeroBet)
+        ));
+        let bet = RouletteBet::new(Bet::Dozen3, 7).unwrap();
+        assert_eq!(
+            bet,
+            RouletteBet {
+                bet: Bet::Dozen3,
+                amount: 7
+            }
+        );
+        assert_eq!(bet.units(), BetUnits::new(7));
     }
 }
@@ -100,3 +100,33 @@ pub fn create_or_allocate_account_raw<'a>(
     )?;
     Ok(())
 }
+
+// Grows an existing account in place, with the payer covering the extra rent
+#[inline(always)]
+pub fn grow_account<'a>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    size: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(size)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        msg!(
+            "Transfer {} lamports to grow the account",
+            required_lamports
+        );
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, required_lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    account_info.realloc(size, true)?;
+    Ok(())
+}
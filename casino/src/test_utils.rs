@@ -0,0 +1,363 @@
+// Accounts and syscall stubs for driving the processor in unit tests
+use crate::instruction::{RandomInstruction, RouletteArgs, SampleArgs, WithdrawFromHoneypotArgs};
+use crate::pda::{self, HoneypotParams};
+use crate::processor::{Honeypot, Processor, HONEYPOT_VERSION, RNG};
+use crate::random_source::FixedRandomSource;
+use crate::state::{RouletteBet, Tokens};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    epoch_schedule::EpochSchedule,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{self, instructions::BorrowedInstruction},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::{
+    instruction::TokenInstruction,
+    state::{Account, AccountState, Mint},
+};
+use std::cell::RefCell;
+use std::sync::Once;
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+    static RETURN_DATA: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+// The stubs are process wide, the state they serve is per test thread
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        CLOCK.with(|clock| unsafe { *(var_addr as *mut Clock) = clock.borrow().clone() });
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut EpochSchedule) = EpochSchedule::default() };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = Some(data.to_vec()));
+    }
+
+    // Token transfers move balances between the packed accounts, every other
+    // CPI is accepted as is
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != spl_token::id() {
+            return Ok(());
+        }
+        if let TokenInstruction::Transfer { amount } = TokenInstruction::unpack(&instruction.data)?
+        {
+            let find = |key: &Pubkey| {
+                account_infos
+                    .iter()
+                    .find(|info| info.key == key)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let source_info = find(&instruction.accounts[0].pubkey)?;
+            let destination_info = find(&instruction.accounts[1].pubkey)?;
+            let mut source = Account::unpack(&source_info.data.borrow())?;
+            let mut destination = Account::unpack(&destination_info.data.borrow())?;
+            source.amount = source
+                .amount
+                .checked_sub(amount)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            destination.amount += amount;
+            Account::pack(source, &mut source_info.data.borrow_mut())?;
+            Account::pack(destination, &mut destination_info.data.borrow_mut())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+}
+
+pub fn set_slot(slot: u64) {
+    CLOCK.with(|clock| clock.borrow_mut().slot = slot);
+}
+
+// Takes whatever the last instruction on this thread returned
+pub fn take_return_data() -> Option<Vec<u8>> {
+    RETURN_DATA.with(|return_data| return_data.borrow_mut().take())
+}
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> TestAccount {
+        TestAccount {
+            key,
+            owner,
+            lamports: 1_000_000_000,
+            data,
+            is_signer: false,
+        }
+    }
+
+    pub fn signer(mut self) -> TestAccount {
+        self.is_signer = true;
+        self
+    }
+
+    pub fn token_account(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> TestAccount {
+        let mut data = vec![0; Account::LEN];
+        let account = Account {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        Account::pack(account, &mut data).unwrap();
+        TestAccount::new(key, spl_token::id(), data)
+    }
+
+    pub fn token_amount(&self) -> u64 {
+        Account::unpack(&self.data).unwrap().amount
+    }
+
+    pub fn mint(key: Pubkey) -> TestAccount {
+        let mut data = vec![0; Mint::LEN];
+        let mint = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        Mint::pack(mint, &mut data).unwrap();
+        TestAccount::new(key, spl_token::id(), data)
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+// A funded table with one gambler, played through Processor::process_with
+// against FixedRandomSource
+pub struct Table {
+    pub program_id: Pubkey,
+    pub rng: TestAccount,
+    pub gambler: TestAccount,
+    pub gambler_token_account: TestAccount,
+    pub mint: TestAccount,
+    pub owner: TestAccount,
+    pub owner_token_account: TestAccount,
+    pub honeypot: TestAccount,
+    pub vault: TestAccount,
+    pub token_program: TestAccount,
+    pub instructions_sysvar: TestAccount,
+    pub system_program: TestAccount,
+}
+
+impl Table {
+    pub const GAMBLER_BALANCE: u64 = 1_000_000;
+    pub const VAULT_BALANCE: u64 = 1_000_000_000;
+
+    pub fn new() -> Table {
+        Table::with_params(1, 0)
+    }
+
+    // Both parameters are honeypot seeds, so they are fixed at creation
+    pub fn with_params(tick_size: u64, minimum_bank_size: u64) -> Table {
+        install_stubs();
+        take_return_data();
+        let program_id = Pubkey::new_unique();
+        let gambler = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let params = HoneypotParams::new(tick_size, 1_000_000, minimum_bank_size);
+        let (rng_key, _) = pda::derive_rng_address(&program_id, &gambler);
+        let (honeypot_key, honeypot_bump_seed) =
+            pda::derive_honeypot_address(&program_id, &mint, &params);
+        let (vault_key, vault_bump_seed) = pda::derive_vault_address(&program_id, &mint, &params);
+        let mut honeypot = Honeypot::try_from_slice(&[0; Honeypot::LEN]).unwrap();
+        honeypot.initialized = true;
+        honeypot.honeypot_bump_seed = honeypot_bump_seed;
+        honeypot.vault_bump_seed = vault_bump_seed;
+        honeypot.owner = owner;
+        honeypot.mint = mint;
+        honeypot.tick_size = tick_size;
+        honeypot.minimum_bank_size = minimum_bank_size;
+        honeypot.max_bet_size = Tokens::new(1_000_000);
+        honeypot.default_tolerance = 10;
+        honeypot.min_reveal_delay = 1;
+        honeypot.version = HONEYPOT_VERSION;
+        let mut instructions =
+            sysvar::instructions::construct_instructions_data(&[BorrowedInstruction {
+                program_id: &program_id,
+                accounts: vec![],
+                data: &[],
+            }]);
+        sysvar::instructions::store_current_index(&mut instructions, 0);
+        Table {
+            program_id,
+            rng: TestAccount::new(rng_key, program_id, vec![0; RNG::LEN]),
+            gambler: TestAccount::new(gambler, system_program::id(), vec![]).signer(),
+            gambler_token_account: TestAccount::token_account(
+                get_associated_token_address(&gambler, &mint),
+                mint,
+                gambler,
+                Table::GAMBLER_BALANCE,
+            ),
+            mint: TestAccount::mint(mint),
+            owner: TestAccount::new(owner, system_program::id(), vec![]).signer(),
+            owner_token_account: TestAccount::token_account(
+                get_associated_token_address(&owner, &mint),
+                mint,
+                owner,
+                0,
+            ),
+            honeypot: TestAccount::new(honeypot_key, program_id, honeypot.try_to_vec().unwrap()),
+            vault: TestAccount::token_account(vault_key, mint, honeypot_key, Table::VAULT_BALANCE),
+            token_program: TestAccount::new(spl_token::id(), Pubkey::default(), vec![]),
+            instructions_sysvar: TestAccount::new(
+                sysvar::instructions::id(),
+                sysvar::id(),
+                instructions,
+            ),
+            system_program: TestAccount::new(system_program::id(), Pubkey::default(), vec![]),
+        }
+    }
+
+    pub fn honeypot(&self) -> Honeypot {
+        Honeypot::try_from_slice(&self.honeypot.data).unwrap()
+    }
+
+    pub fn update_honeypot(&mut self, update: impl FnOnce(&mut Honeypot)) {
+        let mut honeypot = self.honeypot();
+        update(&mut honeypot);
+        self.honeypot.data = honeypot.try_to_vec().unwrap();
+    }
+
+    pub fn rng(&self) -> RNG {
+        RNG::try_from_slice(&self.rng.data).unwrap()
+    }
+
+    pub fn roulette_accounts(&mut self) -> Vec<AccountInfo<'_>> {
+        vec![
+            self.rng.info(),
+            self.gambler.info(),
+            self.gambler_token_account.info(),
+            self.mint.info(),
+            self.honeypot.info(),
+            self.vault.info(),
+            self.token_program.info(),
+            self.instructions_sysvar.info(),
+            self.system_program.info(),
+        ]
+    }
+
+    // Spins with the sample and slot FixedRandomSource hands back
+    pub fn spin(
+        &mut self,
+        sample: u64,
+        slot: u64,
+        tolerance: u64,
+        bets: Vec<RouletteBet>,
+    ) -> ProgramResult {
+        FixedRandomSource::set(sample, slot);
+        let program_id = self.program_id;
+        let accounts = self.roulette_accounts();
+        process(
+            &program_id,
+            &accounts,
+            RandomInstruction::Roulette(RouletteArgs { tolerance, bets }),
+        )
+    }
+
+    // Requests a withdrawal as the honeypot owner at the given slot
+    pub fn request_withdraw(&mut self, amount: u64, slot: u64) -> ProgramResult {
+        set_slot(slot);
+        let program_id = self.program_id;
+        let accounts = [self.honeypot.info(), self.owner.info()];
+        process(
+            &program_id,
+            &accounts,
+            RandomInstruction::RequestWithdraw(WithdrawFromHoneypotArgs {
+                amount_to_withdraw: amount,
+            }),
+        )
+    }
+
+    pub fn withdraw(&mut self, amount: u64, slot: u64) -> ProgramResult {
+        set_slot(slot);
+        let program_id = self.program_id;
+        let accounts = [
+            self.honeypot.info(),
+            self.vault.info(),
+            self.mint.info(),
+            self.owner.info(),
+            self.owner_token_account.info(),
+            self.token_program.info(),
+        ];
+        process(
+            &program_id,
+            &accounts,
+            RandomInstruction::WithdrawFromHoneypot(WithdrawFromHoneypotArgs {
+                amount_to_withdraw: amount,
+            }),
+        )
+    }
+
+    // Samples the gambler's RNG as its owner
+    pub fn sample(&mut self, sample: u64, slot: u64) -> ProgramResult {
+        FixedRandomSource::set(sample, slot);
+        let program_id = self.program_id;
+        let accounts = [self.rng.info(), self.gambler.info()];
+        process(
+            &program_id,
+            &accounts,
+            RandomInstruction::Sample(SampleArgs { tolerance: 10 }),
+        )
+    }
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: RandomInstruction,
+) -> ProgramResult {
+    Processor::process_with::<FixedRandomSource>(
+        program_id,
+        accounts,
+        &instruction.try_to_vec().unwrap(),
+    )
+}
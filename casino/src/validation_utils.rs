@@ -1,12 +1,15 @@
 use super::error::UtilError;
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
+    msg,
     program_error::ProgramError,
     program_option::COption,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::rent::Rent,
+    serialize_utils::read_u16,
+    sysvar::{self, rent::Rent},
 };
 use spl_associated_token_account::get_associated_token_address;
 use spl_token;
@@ -20,6 +23,64 @@ pub fn assert_is_ata(ata: &AccountInfo, wallet: &Pubkey, mint: &Pubkey) -> Progr
     Ok(())
 }
 
+// Like assert_is_ata, but reports a missing ATA as uninitialized instead of
+// failing to unpack it, and hands back the token account
+pub fn assert_is_initialized_ata(
+    ata: &AccountInfo,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Account, ProgramError> {
+    assert_keys_equal(get_associated_token_address(wallet, mint), *ata.key)?;
+    if ata.data_is_empty() {
+        return Err(UtilError::UninitializedAccount.into());
+    }
+    assert_owned_by(ata, &spl_token::id())?;
+    let ata_account: Account = assert_initialized(ata)?;
+    assert_keys_equal(ata_account.owner, *wallet)?;
+    assert_keys_equal(ata_account.mint, *mint)?;
+    Ok(ata_account)
+}
+
+// Returns the index of the executing instruction and the number of
+// instructions in the transaction
+pub fn assert_instructions_sysvar(
+    instruction_sysvar_info: &AccountInfo,
+) -> Result<(u16, u16), ProgramError> {
+    assert_keys_equal(sysvar::instructions::id(), *instruction_sysvar_info.key)?;
+    let current_instruction =
+        sysvar::instructions::load_current_index_checked(instruction_sysvar_info)?;
+    let mut idx = 0;
+    let num_instructions = read_u16(&mut idx, &instruction_sysvar_info.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if current_instruction >= num_instructions {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok((current_instruction, num_instructions))
+}
+
+pub fn assert_not_frozen(token_account_info: &AccountInfo) -> ProgramResult {
+    let token_account: Account = assert_initialized(token_account_info)?;
+    if token_account.is_frozen() {
+        msg!("Token account {} is frozen", token_account_info.key);
+        Err(UtilError::AccountFrozen.into())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_account_count(accounts: &[AccountInfo], expected: usize) -> ProgramResult {
+    if accounts.len() < expected {
+        msg!(
+            "Expected at least {} accounts, got {}",
+            expected,
+            accounts.len()
+        );
+        Err(ProgramError::NotEnoughAccountKeys)
+    } else {
+        Ok(())
+    }
+}
+
 pub fn assert_true(statement: bool) -> ProgramResult {
     if !statement {
         Err(UtilError::StatementFalse.into())
@@ -77,6 +138,49 @@ pub fn assert_mint_authority_matches_mint(
     Ok(())
 }
 
+pub fn assert_data_len(account_info: &AccountInfo, len: usize) -> ProgramResult {
+    if account_info.data_len() < len {
+        msg!(
+            "Account {} holds {} bytes, expected {}",
+            account_info.key,
+            account_info.data_len(),
+            len
+        );
+        Err(ProgramError::AccountDataTooSmall)
+    } else {
+        Ok(())
+    }
+}
+
+// Program accounts that record the layout version they were written with
+pub trait Versioned {
+    fn version(&self) -> u8;
+}
+
+pub fn assert_program_account<T: BorshDeserialize + IsInitialized + Versioned>(
+    account_info: &AccountInfo,
+    program_id: &Pubkey,
+    len: usize,
+    version: u8,
+) -> Result<T, ProgramError> {
+    assert_owned_by(account_info, program_id)?;
+    assert_data_len(account_info, len)?;
+    let account = T::try_from_slice(&account_info.data.borrow())?;
+    if !account.is_initialized() {
+        return Err(UtilError::UninitializedAccount.into());
+    }
+    if account.version() != version {
+        msg!(
+            "Account {} is at version {}, expected {}",
+            account_info.key,
+            account.version(),
+            version
+        );
+        return Err(UtilError::VersionMismatch.into());
+    }
+    Ok(account)
+}
+
 pub fn assert_initialized<T: Pack + IsInitialized>(
     account_info: &AccountInfo,
 ) -> Result<T, ProgramError> {
@@ -95,3 +199,125 @@ pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct Record {
+        initialized: bool,
+        version: u8,
+    }
+
+    impl IsInitialized for Record {
+        fn is_initialized(&self) -> bool {
+            self.initialized
+        }
+    }
+
+    impl Versioned for Record {
+        fn version(&self) -> u8 {
+            self.version
+        }
+    }
+
+    fn load(owned_by_program: bool, version: u8) -> Result<Record, ProgramError> {
+        let program_id = Pubkey::new_unique();
+        let owner = if owned_by_program {
+            program_id
+        } else {
+            Pubkey::new_unique()
+        };
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = Record {
+            initialized: true,
+            version,
+        }
+        .try_to_vec()
+        .unwrap();
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert_program_account(&info, &program_id, 2, 3)
+    }
+
+    #[test]
+    fn program_account_at_the_expected_version_loads() {
+        assert_eq!(load(true, 3).unwrap().version, 3);
+    }
+
+    #[test]
+    fn program_account_with_wrong_owner_is_rejected() {
+        assert_eq!(load(false, 3).err(), Some(UtilError::IncorrectOwner.into()));
+    }
+
+    #[test]
+    fn program_account_with_wrong_version_is_rejected() {
+        for version in [0, 2, 4] {
+            assert_eq!(
+                load(true, version).err(),
+                Some(UtilError::VersionMismatch.into())
+            );
+        }
+    }
+
+    fn instructions_data(count: usize, current: u16) -> Vec<u8> {
+        let program_id = Pubkey::new_unique();
+        let instructions: Vec<_> = (0..count)
+            .map(|_| sysvar::instructions::BorrowedInstruction {
+                program_id: &program_id,
+                accounts: vec![],
+                data: &[],
+            })
+            .collect();
+        let mut data = sysvar::instructions::construct_instructions_data(&instructions);
+        sysvar::instructions::store_current_index(&mut data, current);
+        data
+    }
+
+    fn check_sysvar(key: Pubkey, mut data: Vec<u8>) -> Result<(u16, u16), ProgramError> {
+        let mut lamports = 0;
+        let owner = sysvar::id();
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert_instructions_sysvar(&info)
+    }
+
+    #[test]
+    fn instructions_sysvar_reports_position() {
+        assert_eq!(
+            check_sysvar(sysvar::instructions::id(), instructions_data(3, 1)),
+            Ok((1, 3))
+        );
+    }
+
+    #[test]
+    fn spoofed_instructions_sysvar_is_rejected() {
+        assert_eq!(
+            check_sysvar(Pubkey::new_unique(), instructions_data(1, 0)),
+            Err(UtilError::PublicKeyMismatch.into())
+        );
+        assert_eq!(
+            check_sysvar(sysvar::instructions::id(), instructions_data(1, 1)),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+use solana_program::program_error::ProgramError;
+
+#[derive(Error, Debug, Copy, Clone)]
+pub enum RandomError {
+    #[error("StalePrice")]
+    StalePrice,
+}
+
+impl From<RandomError> for ProgramError {
+    fn from(e: RandomError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -1,9 +1,10 @@
-use hex;
+use crate::error::RandomError;
 use pyth_client;
-use sha256::digest_bytes;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
+    hash::hash,
+    log::sol_log_data,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
@@ -21,24 +22,24 @@ pub fn get_market_prices_and_confindence_interval<'a>(
     let pyth_product = pyth_client::cast::<pyth_client::Product>(pyth_product_data);
     if pyth_product.magic != pyth_client::MAGIC {
         msg!("Pyth product account provided is not a valid Pyth account");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
     if pyth_product.atype != pyth_client::AccountType::Product as u32 {
         msg!("Pyth product account provided is not a valid Pyth product account");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
     if pyth_product.ver != pyth_client::VERSION_2 {
         msg!("Pyth product account provided has a different version than the Pyth client");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
     if !pyth_product.px_acc.is_valid() {
         msg!("Pyth product price account is invalid");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
-    let pyth_price_pubkey = Pubkey::new(&pyth_product.px_acc.val);
+    let pyth_price_pubkey = Pubkey::new_from_array(pyth_product.px_acc.val);
     if &pyth_price_pubkey != pyth_price_info.key {
         msg!("Pyth product price account does not match the Pyth price provided");
-        return Err(ProgramError::InvalidArgument.into());
+        return Err(ProgramError::InvalidArgument);
     }
     let pyth_price_data = &pyth_price_info.try_borrow_data()?;
     let pyth_price = pyth_client::cast::<pyth_client::Price>(pyth_price_data);
@@ -57,9 +58,12 @@ pub fn get_market_prices_and_confindence_interval<'a>(
     {
         Ok((pyth_price.agg.price as u64, pyth_price.agg.conf))
     } else {
-        // For now do nothing, we can modify this later
-        msg!("Tolerance Check Failed");
-        Ok((pyth_price.agg.price as u64, pyth_price.agg.conf))
+        msg!(
+            "Pyth price is stale: published at slot {} with a tolerance of {} slots",
+            pyth_price.agg.pub_slot,
+            slot_tolerance
+        );
+        Err(RandomError::StalePrice.into())
     }
 }
 
@@ -70,14 +74,18 @@ pub fn sample(
     let account_len = pyth_accounts.len();
     if account_len != 7 {
         msg!("Requires exactly 1 clock account and 3 Pyth oracles");
-        return Err(ProgramError::NotEnoughAccountKeys.into());
+        return Err(ProgramError::NotEnoughAccountKeys);
     }
     let account_info_iter = &mut pyth_accounts.iter();
     let clock_account_info = next_account_info(account_info_iter)?;
-    let clock = Clock::from_account_info(clock_account_info)?;
+    // The returned slot is what callers store and compare against to order spins,
+    // so it must come from the real Clock sysvar: check the key before trusting
+    // any of the account's bytes.
     if *clock_account_info.key != sysvar::clock::id() {
-        return Err(ProgramError::InvalidArgument)?;
+        msg!("Expected the clock sysvar, got {}", clock_account_info.key);
+        return Err(ProgramError::InvalidArgument);
     }
+    let clock = Clock::from_account_info(clock_account_info)?;
     let mut seeds: [u8; 56] = [0; 56];
     for i in 0..3 {
         let product_account = next_account_info(account_info_iter)?;
@@ -93,11 +101,10 @@ pub fn sample(
         seeds[start + 8..start + 16].copy_from_slice(&conf.to_le_bytes());
     }
     seeds[48..56].copy_from_slice(&clock.slot.to_le_bytes());
-    let bytes = digest_bytes(&seeds);
-    let hash = match hex::decode(bytes) {
-        Ok(v) => v,
-        Err(_) => return Err(ProgramError::InvalidSeeds.into()),
-    };
+    // Historical Pyth state can't be fetched later, so the exact hash input
+    // is logged for verifiers
+    sol_log_data(&[b"Seed", &seeds]);
+    let hash = hash(&seeds).to_bytes();
     let mut bytes: [u8; 8] = [0; 8];
     bytes.copy_from_slice(&hash[0..8]);
     Ok((u64::from_le_bytes(bytes), clock.slot))
@@ -5,6 +5,19 @@ export const DECIMALS = 6;
 export const TICK_SIZE = new BN(1000000);
 export const MAX_BET_SIZE = new BN(100000000);
 export const MINIMUM_BANK_SIZE = new BN(3500000000);
+export const DEFAULT_TOLERANCE = new BN(10);
+export const MIN_REVEAL_DELAY = new BN(1);
+export const RELOAD_THRESHOLD = new BN(0);
+export const RESTRICT_OWNER_PLAY = 0;
+export const MAX_STRAIGHT_BET = new BN(0);
+export const WITHDRAW_ALL = new BN("18446744073709551615");
+export const ZERO_RULE = 0;
+export const MAX_WIN = new BN(0);
+export const DISABLED_BETS = new BN(0);
+export const WITHDRAW_TIMELOCK_SLOTS = new BN(0);
+export const MAX_DISTINCT_BETS = 0;
+export const MAX_VAULT_BALANCE = new BN(0);
+export const TOLERANCE_UNIT = 0;
 
 // export const RNG_PROGRAM_ID = new PublicKey("rouQqKK4CKYgozmG8fuLTaAt7Crngw3dxsGnrWteuno");
 export const RNG_PROGRAM_ID = new PublicKey("GVKYv6LoQsvjceVasABhzgeDH1nc6RyxovNENLk1uuMG");
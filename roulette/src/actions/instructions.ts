@@ -11,7 +11,7 @@ import { serialize } from "borsh";
 import { InitializeArgs, InitializeHoneypotArgs, RouletteArgs, RouletteBet, SampleArgs, WithdrawFromHoneypotArgs } from "./state";
 import { toPublicKey, StringPublicKey, TOKEN_PROGRAM_ID } from "../utils";
 import { schema } from "./schema";
-import { MAX_BET_SIZE, MINIMUM_BANK_SIZE, RNG_PROGRAM_ID, TICK_SIZE } from "./constants";
+import { DEFAULT_TOLERANCE, DISABLED_BETS, MAX_BET_SIZE, MAX_DISTINCT_BETS, MAX_STRAIGHT_BET, MAX_VAULT_BALANCE, MAX_WIN, MINIMUM_BANK_SIZE, MIN_REVEAL_DELAY, RELOAD_THRESHOLD, RESTRICT_OWNER_PLAY, RNG_PROGRAM_ID, TICK_SIZE, TOLERANCE_UNIT, WITHDRAW_TIMELOCK_SLOTS, ZERO_RULE } from "./constants";
 
 export const initializeInstruction = async (
   rngAccountKey: StringPublicKey,
@@ -75,6 +75,11 @@ export const sampleInstruction = async (
             isSigner: false,
             isWritable: true,
           },
+          {
+            pubkey: toPublicKey(wallet.publicKey),
+            isSigner: true,
+            isWritable: false,
+          },
           {
             pubkey: toPublicKey(SYSVAR_CLOCK_PUBKEY),
             isSigner: false,
@@ -129,6 +134,18 @@ export const initializeHoneypotInstruction = async (
     tickSize: TICK_SIZE,
     maxBetSize: MAX_BET_SIZE,
     minimumBankSize: MINIMUM_BANK_SIZE,
+    defaultTolerance: DEFAULT_TOLERANCE,
+    minRevealDelay: MIN_REVEAL_DELAY,
+    reloadThreshold: RELOAD_THRESHOLD,
+    restrictOwnerPlay: RESTRICT_OWNER_PLAY,
+    maxStraightBet: MAX_STRAIGHT_BET,
+    zeroRule: ZERO_RULE,
+    maxWin: MAX_WIN,
+    disabledBets: DISABLED_BETS,
+    withdrawTimelockSlots: WITHDRAW_TIMELOCK_SLOTS,
+    maxDistinctBets: MAX_DISTINCT_BETS,
+    maxVaultBalance: MAX_VAULT_BALANCE,
+    toleranceUnit: TOLERANCE_UNIT,
   });
   const data = Buffer.from(serialize(schema, settings));
   return {
@@ -263,7 +280,7 @@ export const rouletteInstruction = async (
           {
             pubkey: toPublicKey(wallet.publicKey),
             isSigner: true,
-            isWritable: false,
+            isWritable: true,
           },
           {
             pubkey: toPublicKey(tokenAccount),
@@ -295,6 +312,11 @@ export const rouletteInstruction = async (
             isSigner: false,
             isWritable: false,
           },
+          {
+            pubkey: SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
           {
             pubkey: toPublicKey(SYSVAR_CLOCK_PUBKEY),
             isSigner: false,
@@ -1,5 +1,7 @@
+import { fixedArray } from "../utils/borsh";
 import {
   RNG,
+  Honeypot,
   RouletteBet,
   InitializeArgs,
   SampleArgs,
@@ -35,6 +37,18 @@ export const schema = new Map<any, any>([
         ["tickSize", "u64"],
         ["maxBetSize", "u64"],
         ["minimumBankSize", "u64"],
+        ["defaultTolerance", "u64"],
+        ["minRevealDelay", "u64"],
+        ["reloadThreshold", "u64"],
+        ["restrictOwnerPlay", "u8"],
+        ["maxStraightBet", "u64"],
+        ["zeroRule", "u8"],
+        ["maxWin", "u64"],
+        ["disabledBets", "u64"],
+        ["withdrawTimelockSlots", "u64"],
+        ["maxDistinctBets", "u8"],
+        ["maxVaultBalance", "u64"],
+        ["toleranceUnit", "u8"],
       ],
     },
   ],
@@ -77,6 +91,44 @@ export const schema = new Map<any, any>([
         ["initialized", "u8"],
         ["sample", "u64"],
         ["slot", "u64"],
+        ["commitment", [32]],
+      ],
+    },
+  ],
+  [
+    Honeypot,
+    {
+      kind: "struct",
+      fields: [
+        ["initialized", "u8"],
+        ["honeypotBumpSeed", "u8"],
+        ["vaultBumpSeed", "u8"],
+        ["owner", "pubkeyAsString"],
+        ["mint", "pubkeyAsString"],
+        ["tickSize", "u64"],
+        ["maxBetSize", "u64"],
+        ["minimumBankSize", "u64"],
+        ["defaultTolerance", "u64"],
+        ["paused", "u8"],
+        ["minRevealDelay", "u64"],
+        ["signers", fixedArray("pubkeyAsString", 3)],
+        ["threshold", "u8"],
+        ["reloadThreshold", "u64"],
+        ["oracles", fixedArray("pubkeyAsString", 3)],
+        ["restrictOwnerPlay", "u8"],
+        ["maxStraightBet", "u64"],
+        ["zeroRule", "u8"],
+        ["maxWin", "u64"],
+        ["disabledBets", "u64"],
+        ["withdrawTimelockSlots", "u64"],
+        ["pendingWithdrawal", "u64"],
+        ["withdrawUnlockSlot", "u64"],
+        ["allowedDenominations", fixedArray("u64", 4)],
+        ["maxDistinctBets", "u8"],
+        ["cachedVaultBalance", "u64"],
+        ["maxVaultBalance", "u64"],
+        ["toleranceUnit", "u8"],
+        ["version", "u8"],
       ],
     },
   ],
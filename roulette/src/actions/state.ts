@@ -6,14 +6,17 @@ export class RNG {
   initialized: number;
   sample: BN;
   slot: BN;
+  commitment: Uint8Array;
   constructor(args: {
     initialized: number;
     sample: BN;
     slot: BN;
+    commitment: Uint8Array;
   }) {
     this.initialized = args.initialized;
     this.sample = args.sample;
     this.slot = args.slot;
+    this.commitment = args.commitment;
   }
 }
 
@@ -26,6 +29,27 @@ export class Honeypot {
   tickSize: BN;
   maxBetSize: BN;
   minimumBankSize: BN;
+  defaultTolerance: BN;
+  paused: number;
+  minRevealDelay: BN;
+  signers: StringPublicKey[];
+  threshold: number;
+  reloadThreshold: BN;
+  oracles: StringPublicKey[];
+  restrictOwnerPlay: number;
+  maxStraightBet: BN;
+  zeroRule: number;
+  maxWin: BN;
+  disabledBets: BN;
+  withdrawTimelockSlots: BN;
+  pendingWithdrawal: BN;
+  withdrawUnlockSlot: BN;
+  allowedDenominations: BN[];
+  maxDistinctBets: number;
+  cachedVaultBalance: BN;
+  maxVaultBalance: BN;
+  toleranceUnit: number;
+  version: number;
   constructor(args: {
     initialized: number;
     honeypotBumpSeed: number;
@@ -35,6 +59,27 @@ export class Honeypot {
     tickSize: BN;
     maxBetSize: BN;
     minimumBankSize: BN;
+    defaultTolerance: BN;
+    paused: number;
+    minRevealDelay: BN;
+    signers: StringPublicKey[];
+    threshold: number;
+    reloadThreshold: BN;
+    oracles: StringPublicKey[];
+    restrictOwnerPlay: number;
+    maxStraightBet: BN;
+    zeroRule: number;
+    maxWin: BN;
+    disabledBets: BN;
+    withdrawTimelockSlots: BN;
+    pendingWithdrawal: BN;
+    withdrawUnlockSlot: BN;
+    allowedDenominations: BN[];
+    maxDistinctBets: number;
+    cachedVaultBalance: BN;
+    maxVaultBalance: BN;
+    toleranceUnit: number;
+    version: number;
   }) {
     this.initialized = args.initialized;
     this.honeypotBumpSeed = args.honeypotBumpSeed;
@@ -44,6 +89,27 @@ export class Honeypot {
     this.tickSize = args.tickSize;
     this.maxBetSize = args.maxBetSize;
     this.minimumBankSize = args.minimumBankSize;
+    this.defaultTolerance = args.defaultTolerance;
+    this.paused = args.paused;
+    this.minRevealDelay = args.minRevealDelay;
+    this.signers = args.signers;
+    this.threshold = args.threshold;
+    this.reloadThreshold = args.reloadThreshold;
+    this.oracles = args.oracles;
+    this.restrictOwnerPlay = args.restrictOwnerPlay;
+    this.maxStraightBet = args.maxStraightBet;
+    this.zeroRule = args.zeroRule;
+    this.maxWin = args.maxWin;
+    this.disabledBets = args.disabledBets;
+    this.withdrawTimelockSlots = args.withdrawTimelockSlots;
+    this.pendingWithdrawal = args.pendingWithdrawal;
+    this.withdrawUnlockSlot = args.withdrawUnlockSlot;
+    this.allowedDenominations = args.allowedDenominations;
+    this.maxDistinctBets = args.maxDistinctBets;
+    this.cachedVaultBalance = args.cachedVaultBalance;
+    this.maxVaultBalance = args.maxVaultBalance;
+    this.toleranceUnit = args.toleranceUnit;
+    this.version = args.version;
   }
 }
 
@@ -78,14 +144,50 @@ export class InitializeHoneypotArgs {
   tickSize: BN;
   maxBetSize: BN;
   minimumBankSize: BN;
+  defaultTolerance: BN;
+  minRevealDelay: BN;
+  reloadThreshold: BN;
+  restrictOwnerPlay: number;
+  maxStraightBet: BN;
+  zeroRule: number;
+  maxWin: BN;
+  disabledBets: BN;
+  withdrawTimelockSlots: BN;
+  maxDistinctBets: number;
+  maxVaultBalance: BN;
+  toleranceUnit: number;
   constructor(args: {
     tickSize: BN,
     maxBetSize: BN;
     minimumBankSize: BN;
+    defaultTolerance: BN;
+    minRevealDelay: BN;
+    reloadThreshold: BN;
+    restrictOwnerPlay: number;
+    maxStraightBet: BN;
+    zeroRule: number;
+    maxWin: BN;
+    disabledBets: BN;
+    withdrawTimelockSlots: BN;
+    maxDistinctBets: number;
+    maxVaultBalance: BN;
+    toleranceUnit: number;
   }) {
     this.tickSize = args.tickSize;
     this.maxBetSize = args.maxBetSize;
     this.minimumBankSize = args.minimumBankSize;
+    this.defaultTolerance = args.defaultTolerance;
+    this.minRevealDelay = args.minRevealDelay;
+    this.reloadThreshold = args.reloadThreshold;
+    this.restrictOwnerPlay = args.restrictOwnerPlay;
+    this.maxStraightBet = args.maxStraightBet;
+    this.zeroRule = args.zeroRule;
+    this.maxWin = args.maxWin;
+    this.disabledBets = args.disabledBets;
+    this.withdrawTimelockSlots = args.withdrawTimelockSlots;
+    this.maxDistinctBets = args.maxDistinctBets;
+    this.maxVaultBalance = args.maxVaultBalance;
+    this.toleranceUnit = args.toleranceUnit;
   }
 }
 
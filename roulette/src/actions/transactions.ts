@@ -167,6 +167,64 @@ export const mintChips = async (
   }
 };
 
+export interface HoneypotParams {
+  tickSize: BN;
+  maxBetSize: BN;
+  minimumBankSize: BN;
+}
+
+// The table this app creates and plays against
+export const DEFAULT_HONEYPOT_PARAMS: HoneypotParams = {
+  tickSize: TICK_SIZE,
+  maxBetSize: MAX_BET_SIZE,
+  minimumBankSize: MINIMUM_BANK_SIZE,
+};
+
+const honeypotParamSeeds = (mint: PublicKey, params: HoneypotParams) => [
+  mint.toBuffer(),
+  new Uint8Array(params.tickSize.toArray("le", 8)),
+  new Uint8Array(params.maxBetSize.toArray("le", 8)),
+  new Uint8Array(params.minimumBankSize.toArray("le", 8)),
+];
+
+export const findHoneypotAddress = async (
+  mint: PublicKey,
+  params: HoneypotParams
+) => {
+  return await PublicKey.findProgramAddress(
+    [Buffer.from("honeypot"), ...honeypotParamSeeds(mint, params)],
+    RNG_PROGRAM_ID
+  );
+};
+
+export const findVaultAddress = async (
+  mint: PublicKey,
+  params: HoneypotParams
+) => {
+  return await PublicKey.findProgramAddress(
+    [Buffer.from("vault"), ...honeypotParamSeeds(mint, params)],
+    RNG_PROGRAM_ID
+  );
+};
+
+// Honeypots are keyed on their params, so tables can only be discovered by
+// deriving each candidate and checking whether an account exists there
+export const enumerateHoneypots = async (
+  connection: Connection,
+  mint: PublicKey,
+  paramCandidates: HoneypotParams[]
+) => {
+  let found: { honeypotKey: PublicKey; params: HoneypotParams }[] = [];
+  for (const params of paramCandidates) {
+    let [honeypotKey, _bumpSeed] = await findHoneypotAddress(mint, params);
+    let res = await connection.getAccountInfo(honeypotKey);
+    if (res && res.owner.equals(RNG_PROGRAM_ID) && res.data[0] === 1) {
+      found.push({ honeypotKey, params });
+    }
+  }
+  return found;
+};
+
 export const initializeHoneypot = async (
   connection,
   wallet: any,
@@ -192,25 +250,13 @@ export const initializeHoneypot = async (
   if (!mintAccount) {
     return false;
   }
-  let [honeypotKey, _honeypotBumpSeed] = await PublicKey.findProgramAddress(
-    [
-      Buffer.from("honeypot"),
-      mintAccount.toBuffer(),
-      new Uint8Array(TICK_SIZE.toArray("le", 8)),
-      new Uint8Array(MAX_BET_SIZE.toArray("le", 8)),
-      new Uint8Array(MINIMUM_BANK_SIZE.toArray("le", 8)),
-    ],
-    RNG_PROGRAM_ID
+  let [honeypotKey, _honeypotBumpSeed] = await findHoneypotAddress(
+    mintAccount,
+    DEFAULT_HONEYPOT_PARAMS
   );
-  let [vaultKey, _vaultBumpSeed] = await PublicKey.findProgramAddress(
-    [
-      Buffer.from("vault"),
-      mintAccount.toBuffer(),
-      new Uint8Array(TICK_SIZE.toArray("le", 8)),
-      new Uint8Array(MAX_BET_SIZE.toArray("le", 8)),
-      new Uint8Array(MINIMUM_BANK_SIZE.toArray("le", 8)),
-    ],
-    RNG_PROGRAM_ID
+  let [vaultKey, _vaultBumpSeed] = await findVaultAddress(
+    mintAccount,
+    DEFAULT_HONEYPOT_PARAMS
   );
 
   let res = await connection.getAccountInfo(honeypotKey);
@@ -301,6 +347,7 @@ export const sample = async (
   }
   let bets: RouletteBet[] = [];
   for (let [bet, amount] of Object.entries(betTrackerCtx.state)) {
+    if (!amount) continue;
     bets.push(
       new RouletteBet({
         bet: BET_TO_IDX[bet],
@@ -335,25 +382,13 @@ export const sample = async (
     );
   }
 
-  let [honeypotKey, _honeypotBumpSeed] = await PublicKey.findProgramAddress(
-    [
-      Buffer.from("honeypot"),
-      mintAccount.toBuffer(),
-      new Uint8Array(TICK_SIZE.toArray("le", 8)),
-      new Uint8Array(MAX_BET_SIZE.toArray("le", 8)),
-      new Uint8Array(MINIMUM_BANK_SIZE.toArray("le", 8)),
-    ],
-    RNG_PROGRAM_ID
+  let [honeypotKey, _honeypotBumpSeed] = await findHoneypotAddress(
+    mintAccount,
+    DEFAULT_HONEYPOT_PARAMS
   );
-  let [vaultKey, _vaultBumpSeed] = await PublicKey.findProgramAddress(
-    [
-      Buffer.from("vault"),
-      mintAccount.toBuffer(),
-      new Uint8Array(TICK_SIZE.toArray("le", 8)),
-      new Uint8Array(MAX_BET_SIZE.toArray("le", 8)),
-      new Uint8Array(MINIMUM_BANK_SIZE.toArray("le", 8)),
-    ],
-    RNG_PROGRAM_ID
+  let [vaultKey, _vaultBumpSeed] = await findVaultAddress(
+    mintAccount,
+    DEFAULT_HONEYPOT_PARAMS
   );
 
   console.log(bets);
@@ -30,3 +30,30 @@ export const extendBorsh = () => {
 };
 
 extendBorsh();
+
+const capitalize = (type: string) => type.charAt(0).toUpperCase() + type.slice(1);
+
+// borsh-js only supports fixed-size arrays of u8, so register a named
+// reader/writer pair for `[type; len]` and return the name for use in a schema.
+export const fixedArray = (type: string, len: number) => {
+  const name = `${type}Array${len}`;
+  const read = `read${capitalize(type)}`;
+  const write = `write${capitalize(type)}`;
+
+  (BinaryReader.prototype as any)[`read${capitalize(name)}`] = function () {
+    const reader = this as any;
+    return Array.from({ length: len }, () => reader[read]());
+  };
+
+  (BinaryWriter.prototype as any)[`write${capitalize(name)}`] = function (
+    value: any[],
+  ) {
+    const writer = this as any;
+    if (value.length !== len) {
+      throw new Error(`Expected ${len} elements, got ${value.length}`);
+    }
+    value.forEach(v => writer[write](v));
+  };
+
+  return name;
+};